@@ -0,0 +1,85 @@
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const DEFAULT_RESPONSE_MISMATCH: Rule = Rule {
+    name: "default-response-mismatch",
+    severity: Severity::Warning,
+};
+
+/// Report operations whose `default` response body differs from the one most operations use.
+///
+/// The catch-all response usually carries the API's error format, so it should be the same
+/// everywhere. Descriptions and headers may differ, only the content is compared.
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    // canonical content => operations using it, as (location, method and path)
+    let mut groups: IndexMap<String, Vec<(String, String)>> = IndexMap::new();
+
+    // the visitors are written for mutable slots, so walk a copy
+    let mut document = openapi.clone();
+    let _ = walk::all_path_items_mut(&mut document, config, &mut |origin, item| {
+        for (method, operation) in item.iter() {
+            let Some(default) = operation.responses.default.as_ref() else {
+                continue;
+            };
+
+            let Some(response) = walk::resolve_response(&components, default) else {
+                continue;
+            };
+
+            let Ok(key) = canonical_json(&response.content) else {
+                continue;
+            };
+
+            groups.entry(key).or_default().push((
+                origin
+                    .operation(method, operation)
+                    .child(&["responses", "default"])
+                    .pointer,
+                format!(
+                    "{} {}",
+                    method.to_uppercase(),
+                    origin.path.as_deref().unwrap_or_default()
+                ),
+            ));
+        }
+
+        Ok(())
+    });
+
+    // the most used content, the earliest one on a tie
+    let Some(common) = groups
+        .values()
+        .rev()
+        .max_by_key(|operations| operations.len())
+    else {
+        return;
+    };
+    let common = common.clone();
+
+    for operations in groups.values() {
+        if *operations == common {
+            continue;
+        }
+
+        for (location, _) in operations.iter() {
+            report.add(
+                &DEFAULT_RESPONSE_MISMATCH,
+                location.clone(),
+                format!(
+                    "Default response schema differs from the one {} other operations use, like {}",
+                    common.len(),
+                    common[0].1
+                ),
+            );
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use indexmap::IndexMap;
+use openapiv3::Components;
+use openapiv3::Discriminator;
+use openapiv3::ObjectType;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+
+use crate::config::Config;
+use crate::passes::all_of;
+use crate::passes::union;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_SCHEMA;
+
+pub const MISSING_DISCRIMINATOR: Rule = Rule {
+    name: "missing-discriminator",
+    severity: Severity::Info,
+};
+
+pub const UNTAGGED_UNION: Rule = Rule {
+    name: "untagged-union",
+    severity: Severity::Info,
+};
+
+pub const INVALID_DISCRIMINATOR: Rule = Rule {
+    name: "invalid-discriminator",
+    severity: Severity::Error,
+};
+
+/// Propose a discriminator for every `oneOf` without one, or report why none can be inferred
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas(openapi, config, &mut |origin, schema| {
+        let SchemaKind::OneOf { one_of } = &schema.schema_kind else {
+            return;
+        };
+
+        // a member and `{type: null}` is a nullable schema rather than a union
+        let tagged = one_of
+            .iter()
+            .filter(|member| !union::is_null(member))
+            .count();
+        if schema.schema_data.discriminator.is_some() || tagged < 2 {
+            return;
+        }
+
+        match infer(one_of, &components) {
+            Ok(discriminator) => report.add(
+                &MISSING_DISCRIMINATOR,
+                origin.pointer.clone(),
+                format!(
+                    "oneOf can be discriminated by property {}: {}",
+                    discriminator.property_name,
+                    discriminator
+                        .mapping
+                        .iter()
+                        .map(|(value, reference)| format!("{} => {}", value, reference))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            ),
+            Err(reason) => report.add(
+                &UNTAGGED_UNION,
+                origin.pointer.clone(),
+                format!("No discriminator can be inferred for oneOf, {}", reason),
+            ),
+        }
+    });
+}
+
+/// Check every discriminator: mapping targets resolve and are members of the union, members
+/// require the property and no two members accept the same value
+pub fn validate(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas(openapi, config, &mut |origin, schema| {
+        let Some(discriminator) = schema.schema_data.discriminator.as_ref() else {
+            return;
+        };
+
+        let location = origin.child(&["discriminator"]).pointer;
+        let property = discriminator.property_name.as_str();
+
+        let (members, is_union) = match &schema.schema_kind {
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => {
+                (members.clone(), true)
+            }
+            SchemaKind::Any(any) if !any.one_of.is_empty() || !any.any_of.is_empty() => {
+                ([any.one_of.clone(), any.any_of.clone()].concat(), true)
+            }
+            // a discriminator on a base schema, its subtypes extend it with allOf
+            _ => (vec![ReferenceOr::Item(schema.clone())], false),
+        };
+        // `{type: null}` members only make the union nullable
+        let members: Vec<_> = members
+            .into_iter()
+            .filter(|member| !union::is_null(member))
+            .collect();
+
+        for (value, target) in discriminator.mapping.iter() {
+            let reference = if target.starts_with('#') {
+                target.clone()
+            } else {
+                component_ref(COMPONENT_SCHEMA, target)
+            };
+
+            if walk::resolve_schema(&components, &reference).is_none() {
+                report.add(
+                    &INVALID_DISCRIMINATOR,
+                    location.clone(),
+                    format!("Mapping {} => {} doesn't resolve", value, target),
+                );
+            } else if is_union
+                && !members.iter().any(|member| {
+                    matches!(member, ReferenceOr::Reference { reference: member } if *member == reference)
+                })
+            {
+                report.add(
+                    &INVALID_DISCRIMINATOR,
+                    location.clone(),
+                    format!(
+                        "Mapping {} => {} targets a schema that isn't a member of the union",
+                        value, target
+                    ),
+                );
+            }
+        }
+
+        // member name => values it accepts for the property
+        let mut tags: Vec<(String, Vec<String>)> = Vec::new();
+
+        for (index, member) in members.iter().enumerate() {
+            let (name, object) = match member {
+                ReferenceOr::Reference { reference } => (
+                    reference.clone(),
+                    walk::resolve_schema(&components, reference)
+                        .and_then(|schema| all_of::flattened(schema, &components)),
+                ),
+                ReferenceOr::Item(member) => (
+                    format!("#{}", index),
+                    all_of::flattened(member, &components),
+                ),
+            };
+
+            // members that aren't objects can't carry the property, let alone require it
+            let Some(object) = object else {
+                report.add(
+                    &INVALID_DISCRIMINATOR,
+                    location.clone(),
+                    format!("Member {} isn't an object schema", name),
+                );
+                continue;
+            };
+
+            if !object.required.iter().any(|required| required == property) {
+                report.add(
+                    &INVALID_DISCRIMINATOR,
+                    location.clone(),
+                    format!("Member {} doesn't declare {} as required", name, property),
+                );
+            }
+
+            if let Some(slot) = object.properties.get(property) {
+                tags.push((name, tag_values(slot, &components)));
+            }
+        }
+
+        for (index, (a, a_values)) in tags.iter().enumerate() {
+            for (b, b_values) in tags.iter().skip(index + 1) {
+                for value in a_values.iter().filter(|value| b_values.contains(value)) {
+                    report.add(
+                        &INVALID_DISCRIMINATOR,
+                        location.clone(),
+                        format!(
+                            "Members {} and {} share the value {} of {}",
+                            a, b, value, property
+                        ),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// A discriminator for a union of referenced object schemas that all require a property
+/// holding values no other member uses. `{type: null}` members are left out.
+pub fn infer(
+    members: &[ReferenceOr<Schema>],
+    components: &Components,
+) -> Result<Discriminator, String> {
+    let mut objects: Vec<(&str, ObjectType)> = Vec::new();
+
+    for (index, member) in members.iter().enumerate() {
+        if union::is_null(member) {
+            continue;
+        }
+
+        let ReferenceOr::Reference { reference } = member else {
+            return Err(format!(
+                "member {} is inline, a discriminator mapping needs references",
+                index
+            ));
+        };
+
+        let object = walk::resolve_schema(components, reference)
+            .and_then(|schema| all_of::flattened(schema, components))
+            .ok_or_else(|| format!("{} is not an object schema", reference))?;
+
+        objects.push((reference, object));
+    }
+
+    if objects.len() < 2 {
+        return Err("it has fewer than two members besides null".to_string());
+    }
+    let (_, first) = &objects[0];
+
+    first
+        .required
+        .iter()
+        .find_map(|property| {
+            mapping(property, &objects, components).map(|mapping| Discriminator {
+                property_name: property.clone(),
+                mapping,
+                extensions: IndexMap::new(),
+            })
+        })
+        .ok_or_else(|| "no required property has values distinct to each member".to_string())
+}
+
+/// Value => member reference, when every member requires `property` with values of its own
+fn mapping(
+    property: &str,
+    objects: &[(&str, ObjectType)],
+    components: &Components,
+) -> Option<IndexMap<String, String>> {
+    let mut mapping = IndexMap::new();
+
+    for (reference, object) in objects.iter() {
+        if !object.required.iter().any(|required| required == property) {
+            return None;
+        }
+
+        let values = tag_values(object.properties.get(property)?, components);
+        if values.is_empty() {
+            return None;
+        }
+
+        for value in values {
+            if mapping.insert(value, reference.to_string()).is_some() {
+                return None;
+            }
+        }
+    }
+
+    Some(mapping)
+}
+
+/// The string values a property is restricted to by its `enum`
+fn tag_values(slot: &ReferenceOr<Box<Schema>>, components: &Components) -> Vec<String> {
+    let schema = match slot {
+        ReferenceOr::Reference { reference } => walk::resolve_schema(components, reference),
+        ReferenceOr::Item(schema) => Some(schema.as_ref()),
+    };
+
+    match schema.map(|schema| &schema.schema_kind) {
+        Some(SchemaKind::Type(Type::String(string))) => {
+            string.enumeration.iter().flatten().cloned().collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn findings(
+        schemas: Value,
+        check: fn(&OpenAPI, &Config, &mut Report),
+    ) -> Vec<(&'static str, String, String)> {
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": schemas}
+        }))
+        .unwrap();
+
+        let mut report = Report::default();
+        check(&openapi, &Config::default(), &mut report);
+
+        report
+            .findings()
+            .iter()
+            .map(|finding| {
+                (
+                    finding.rule,
+                    finding.location.clone(),
+                    finding.message.clone(),
+                )
+            })
+            .collect()
+    }
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/schemas/{}", name)})
+    }
+
+    fn tagged(kinds: &[&str]) -> Value {
+        json!({
+            "type": "object",
+            "required": ["kind", "name"],
+            "properties": {
+                "kind": {"type": "string", "enum": kinds},
+                "name": {"type": "string"}
+            }
+        })
+    }
+
+    #[test]
+    fn infers_a_discriminator_or_explains_why_not() {
+        let found = findings(
+            json!({
+                "Cat": tagged(&["cat"]),
+                "Dog": tagged(&["dog", "puppy"]),
+                "Lion": tagged(&["cat"]),
+                "Pet": {"oneOf": [reference("Cat"), reference("Dog")]},
+                "Clash": {"oneOf": [reference("Cat"), reference("Lion")]},
+                "Inline": {"oneOf": [reference("Cat"), {"type": "object"}]},
+                "MaybeCat": {"oneOf": [reference("Cat"), {"type": "null"}]}
+            }),
+            run,
+        );
+
+        assert_eq!(
+            found,
+            [
+                (
+                    "missing-discriminator",
+                    "#/components/schemas/Pet".to_string(),
+                    "oneOf can be discriminated by property kind: cat => #/components/schemas/Cat, dog => #/components/schemas/Dog, puppy => #/components/schemas/Dog".to_string()
+                ),
+                (
+                    "untagged-union",
+                    "#/components/schemas/Clash".to_string(),
+                    "No discriminator can be inferred for oneOf, no required property has values distinct to each member".to_string()
+                ),
+                (
+                    "untagged-union",
+                    "#/components/schemas/Inline".to_string(),
+                    "No discriminator can be inferred for oneOf, member 1 is inline, a discriminator mapping needs references".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_mappings_and_members() {
+        let found = findings(
+            json!({
+                "Cat": tagged(&["cat"]),
+                "Dog": tagged(&["dog"]),
+                "Lion": tagged(&["cat"]),
+                "Named": {"type": "object", "properties": {"name": {"type": "string"}}},
+                "Pet": {
+                    "oneOf": [reference("Cat"), reference("Dog"), {"type": "null"}],
+                    "discriminator": {"propertyName": "kind", "mapping": {
+                        "cat": "Cat",
+                        "dog": "#/components/schemas/Dog",
+                        "bird": "#/components/schemas/Bird",
+                        "lion": "#/components/schemas/Lion"
+                    }}
+                },
+                "Loose": {
+                    "oneOf": [reference("Cat"), reference("Lion"), reference("Named")],
+                    "discriminator": {"propertyName": "kind"}
+                }
+            }),
+            validate,
+        );
+
+        let location = |name: &str| format!("#/components/schemas/{}/discriminator", name);
+        assert_eq!(
+            found
+                .into_iter()
+                .map(|(rule, location, message)| {
+                    assert_eq!(rule, "invalid-discriminator");
+                    (location, message)
+                })
+                .collect::<Vec<_>>(),
+            [
+                (
+                    location("Pet"),
+                    "Mapping bird => #/components/schemas/Bird doesn't resolve".to_string()
+                ),
+                (
+                    location("Pet"),
+                    "Mapping lion => #/components/schemas/Lion targets a schema that isn't a member of the union".to_string()
+                ),
+                (
+                    location("Loose"),
+                    "Member #/components/schemas/Named doesn't declare kind as required".to_string()
+                ),
+                (
+                    location("Loose"),
+                    "Members #/components/schemas/Cat and #/components/schemas/Lion share the value cat of kind".to_string()
+                ),
+            ]
+        );
+    }
+}
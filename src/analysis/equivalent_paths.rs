@@ -0,0 +1,92 @@
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+
+use crate::config::Config;
+use crate::report::pointer;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+
+pub const EQUIVALENT_PATHS: Rule = Rule {
+    name: "equivalent-paths",
+    severity: Severity::Warning,
+};
+
+/// Report paths that route to the same place as an earlier one, differing only in the names of
+/// their variables or a trailing slash
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    // normalized template => paths using it
+    let mut groups: IndexMap<String, Vec<&String>> = IndexMap::new();
+
+    for path in openapi.paths.paths.keys() {
+        if config.is_excluded(path) {
+            continue;
+        }
+
+        groups.entry(normalize(path)).or_default().push(path);
+    }
+
+    for paths in groups.values() {
+        for path in paths.iter().skip(1) {
+            report.add(
+                &EQUIVALENT_PATHS,
+                pointer(&["paths", path]),
+                format!("Routes to the same place as {}", paths[0]),
+            );
+        }
+    }
+}
+
+/// Literal text between the variables of a path template, and the variable names
+pub fn template_parts(path: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut literals = Vec::new();
+    let mut variables = Vec::new();
+    let mut rest = path;
+
+    while let Some((literal, variable, after)) = rest
+        .split_once('{')
+        .and_then(|(literal, after)| after.split_once('}').map(|(v, a)| (literal, v, a)))
+    {
+        literals.push(literal);
+        variables.push(variable);
+        rest = after;
+    }
+    literals.push(rest);
+
+    (literals, variables)
+}
+
+/// The template with variables unnamed and without a trailing slash, `/users/{id}/` becomes
+/// `/users/{}`
+pub fn normalize(path: &str) -> String {
+    let normalized = template_parts(path).0.join("{}");
+
+    match normalized.strip_suffix('/') {
+        Some(trimmed) if !trimmed.is_empty() => trimmed.to_string(),
+        _ => normalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_parts_splits_literals_and_variables() {
+        assert_eq!(
+            template_parts("/users/{id}/orders/{orderId}"),
+            (vec!["/users/", "/orders/", ""], vec!["id", "orderId"])
+        );
+        assert_eq!(template_parts("/users"), (vec!["/users"], vec![]));
+        // an unclosed brace is literal text
+        assert_eq!(template_parts("/a/{b"), (vec!["/a/{b"], vec![]));
+    }
+
+    #[test]
+    fn normalize_drops_names_and_trailing_slash() {
+        assert_eq!(normalize("/users/{userId}/"), "/users/{}");
+        assert_eq!(normalize("/users/{id}"), normalize("/users/{userId}/"));
+        assert_eq!(normalize("/"), "/");
+        assert_ne!(normalize("/users/{id}"), normalize("/users/id"));
+    }
+}
@@ -0,0 +1,348 @@
+use openapiv3::AdditionalProperties;
+use openapiv3::Components;
+use openapiv3::LinkOperation;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Response;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const UNRESOLVED_LINK: Rule = Rule {
+    name: "unresolved-link",
+    severity: Severity::Error,
+};
+
+pub const UNKNOWN_LINK_PARAMETER: Rule = Rule {
+    name: "unknown-link-parameter",
+    severity: Severity::Error,
+};
+
+pub const INVALID_LINK_EXPRESSION: Rule = Rule {
+    name: "invalid-link-expression",
+    severity: Severity::Error,
+};
+
+/// How deep to follow compositions and references before giving up on a property
+const MAX_DEPTH: usize = 32;
+
+/// The parameters, by location and name, of an operation a link can target
+type Target = Vec<(&'static str, String)>;
+
+/// Check the links of every response: the target operation resolves, the link's parameters are
+/// parameters of that operation, and `$response.body#/...` expressions name properties of the
+/// response body.
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+    let (by_id, by_ref) = targets(openapi, &components);
+
+    // the visitors are written for mutable slots, so walk a copy
+    let mut document = openapi.clone();
+    let _ = walk::all_responses_mut(&mut document, config, &mut |origin, slot| {
+        // referenced responses are checked where they are defined
+        let ReferenceOr::Item(response) = slot else {
+            return Ok(());
+        };
+
+        for (name, link) in response.links.iter() {
+            let Some(link) = walk::resolve_link(&components, link) else {
+                continue;
+            };
+
+            let link_origin = origin.child(&["links", name]);
+            let location = link_origin.pointer.clone();
+
+            let target = match &link.operation {
+                LinkOperation::OperationId(id) => by_id
+                    .get(id)
+                    .ok_or_else(|| format!("No operation has operationId {}", id)),
+                // operations of other documents can't be checked
+                LinkOperation::OperationRef(reference) if !reference.starts_with('#') => continue,
+                LinkOperation::OperationRef(reference) => by_ref
+                    .get(reference)
+                    .ok_or_else(|| format!("operationRef {} is not an operation", reference)),
+            };
+
+            match target {
+                Ok(target) => {
+                    for key in link.parameters.keys() {
+                        if !accepts(target, key) {
+                            report.add(
+                                &UNKNOWN_LINK_PARAMETER,
+                                link_origin.child(&["parameters", key]).pointer,
+                                format!("The linked operation has no parameter {}", key),
+                            );
+                        }
+                    }
+                }
+                Err(message) => report.add(&UNRESOLVED_LINK, location.clone(), message),
+            }
+
+            let values = link.parameters.values().chain(link.request_body.iter());
+            for expression in values.flat_map(expressions) {
+                let Some(body_pointer) = expression.strip_prefix("$response.body") else {
+                    continue;
+                };
+
+                if let Some(problem) = check_body(&components, response, body_pointer) {
+                    report.add(
+                        &INVALID_LINK_EXPRESSION,
+                        location.clone(),
+                        format!("{} {}", expression, problem),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    });
+}
+
+/// Parameters of every operation, by operationId and by JSON pointer, path-level parameters
+/// included
+fn targets(
+    openapi: &OpenAPI,
+    components: &Components,
+) -> (HashMap<String, Target>, HashMap<String, Target>) {
+    let mut by_id = HashMap::new();
+    let mut by_ref = HashMap::new();
+
+    // links may target excluded paths too
+    let mut document = openapi.clone();
+    let _ = walk::all_path_items_mut(&mut document, &Config::default(), &mut |origin, item| {
+        for (method, operation) in item.iter() {
+            let target: Target = item
+                .parameters
+                .iter()
+                .chain(operation.parameters.iter())
+                .filter_map(|param| walk::resolve_parameter(components, param))
+                .map(walk::parameter_key)
+                .collect();
+
+            if let Some(id) = operation.operation_id.as_ref() {
+                by_id.insert(id.clone(), target.clone());
+            }
+            by_ref.insert(origin.operation(method, operation).pointer, target);
+        }
+
+        Ok(())
+    });
+
+    (by_id, by_ref)
+}
+
+/// Whether a link parameter key, `name` or `{in}.name`, is a parameter of the target
+fn accepts(target: &Target, key: &str) -> bool {
+    target.iter().any(|(location, name)| {
+        name == key
+            || key
+                .split_once('.')
+                .is_some_and(|(in_, rest)| in_ == *location && rest == name)
+    })
+}
+
+/// Runtime expressions in a link value, the whole string or `{...}` parts of it
+fn expressions(value: &Value) -> Vec<&str> {
+    let Value::String(text) = value else {
+        return Vec::new();
+    };
+
+    if text.starts_with('$') {
+        return vec![text.as_str()];
+    }
+
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(expression, _)| expression))
+        .filter(|expression| expression.starts_with('$'))
+        .collect()
+}
+
+/// What is wrong with a `$response.body` pointer, if anything, the part after `$response.body`
+fn check_body(components: &Components, response: &Response, body_pointer: &str) -> Option<String> {
+    if response.content.is_empty() {
+        return Some("refers to the body of a response without one".to_string());
+    }
+
+    let schemas: Vec<&Schema> = response
+        .content
+        .values()
+        .filter_map(|media| media.schema.as_ref())
+        .filter_map(|schema| resolve(components, schema))
+        .collect();
+
+    let pointer = body_pointer.strip_prefix('#')?;
+
+    let tokens: Vec<String> = pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect();
+
+    // a body without a schema may be anything
+    if schemas.len() < response.content.len()
+        || schemas
+            .iter()
+            .any(|schema| has_property(components, schema, &tokens, 0))
+    {
+        return None;
+    }
+
+    Some("names a property the response body doesn't have".to_string())
+}
+
+fn resolve<'a>(components: &'a Components, slot: &'a ReferenceOr<Schema>) -> Option<&'a Schema> {
+    match slot {
+        ReferenceOr::Item(schema) => Some(schema),
+        ReferenceOr::Reference { reference } => walk::resolve_schema(components, reference),
+    }
+}
+
+fn resolve_boxed<'a>(
+    components: &'a Components,
+    slot: &'a ReferenceOr<Box<Schema>>,
+) -> Option<&'a Schema> {
+    match slot {
+        ReferenceOr::Item(schema) => Some(schema),
+        ReferenceOr::Reference { reference } => walk::resolve_schema(components, reference),
+    }
+}
+
+/// Whether the pointer tokens lead to a property or item of the schema.
+///
+/// Whatever can't be followed, such as external references or untyped schemas, counts as
+/// having it.
+fn has_property(components: &Components, schema: &Schema, tokens: &[String], depth: usize) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return true;
+    };
+
+    if depth > MAX_DEPTH {
+        return true;
+    }
+
+    let follow = |slot: &ReferenceOr<Box<Schema>>, tokens: &[String]| {
+        resolve_boxed(components, slot)
+            .is_none_or(|schema| has_property(components, schema, tokens, depth + 1))
+    };
+
+    let compose = |members: &Vec<ReferenceOr<Schema>>| {
+        members.iter().any(|member| {
+            resolve(components, member)
+                .is_none_or(|schema| has_property(components, schema, tokens, depth + 1))
+        })
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(object)) => match object.properties.get(token) {
+            Some(property) => follow(property, rest),
+            None => matches!(
+                object.additional_properties,
+                Some(AdditionalProperties::Any(true)) | Some(AdditionalProperties::Schema(_))
+            ),
+        },
+        SchemaKind::Type(Type::Array(array)) => {
+            token.parse::<usize>().is_ok()
+                && array.items.as_ref().is_none_or(|items| follow(items, rest))
+        }
+        SchemaKind::Type(_) => false,
+        SchemaKind::OneOf { one_of: members }
+        | SchemaKind::AnyOf { any_of: members }
+        | SchemaKind::AllOf { all_of: members } => compose(members),
+        SchemaKind::Any(any) => match any.properties.get(token) {
+            Some(property) => follow(property, rest),
+            None => any.properties.is_empty(),
+        },
+        SchemaKind::Not { .. } => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn link(operation: (&str, &str), parameters: Value) -> Value {
+        json!({operation.0: operation.1, "parameters": parameters})
+    }
+
+    #[test]
+    fn checks_targets_parameters_and_body_expressions() {
+        let get_user = ("operationId", "getUser");
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/users/{id}": {"get": {
+                    "operationId": "getUser",
+                    "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}],
+                    "responses": {}
+                }},
+                "/users": {"post": {"responses": {
+                    "201": {
+                        "description": "created",
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}},
+                        "links": {
+                            "Valid": link(get_user, json!({"id": "$response.body#/id"})),
+                            "ByRef": link(
+                                ("operationRef", "#/paths/~1users~1{id}/get"),
+                                json!({"path.id": "user-{$response.body#/name}"})
+                            ),
+                            "External": link(("operationRef", "other.yaml#/paths/~1x/get"), json!({"x": 1})),
+                            "Unresolved": link(("operationId", "deleteUser"), json!({})),
+                            "UnknownParameter": link(get_user, json!({"userId": "$response.body#/id"})),
+                            "UnknownProperty": link(get_user, json!({"id": "$response.body#/uuid"}))
+                        }
+                    },
+                    "202": {
+                        "description": "accepted",
+                        "links": {"NoBody": link(get_user, json!({"id": "$response.body#/id"}))}
+                    }
+                }}}
+            },
+            "components": {"schemas": {"User": {
+                "type": "object",
+                "properties": {"id": {"type": "string"}, "name": {"type": "string"}}
+            }}}
+        }))
+        .unwrap();
+
+        let mut report = Report::default();
+        run(&openapi, &Config::default(), &mut report);
+
+        let findings: Vec<(&str, &str)> = report
+            .findings()
+            .iter()
+            .map(|finding| (finding.rule, finding.location.as_str()))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                (
+                    "unresolved-link",
+                    "#/paths/~1users/post/responses/201/links/Unresolved"
+                ),
+                (
+                    "unknown-link-parameter",
+                    "#/paths/~1users/post/responses/201/links/UnknownParameter/parameters/userId"
+                ),
+                (
+                    "invalid-link-expression",
+                    "#/paths/~1users/post/responses/201/links/UnknownProperty"
+                ),
+                (
+                    "invalid-link-expression",
+                    "#/paths/~1users/post/responses/202/links/NoBody"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+pub mod default_response;
+pub mod discriminator;
+pub mod equivalent_paths;
+pub mod links;
+pub mod nullable;
+pub mod overlap;
+pub mod path_parameters;
+pub mod similarity;
+
+use openapiv3::OpenAPI;
+
+use crate::config::Config;
+use crate::report::Report;
+
+/// Read-only checks run on the input schema, next to the scan
+pub const ANALYSES: &[fn(&OpenAPI, &Config, &mut Report)] = &[
+    similarity::run,
+    discriminator::run,
+    discriminator::validate,
+    overlap::run,
+    nullable::run,
+    default_response::run,
+    path_parameters::run,
+    equivalent_paths::run,
+    links::run,
+];
+
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    for analysis in ANALYSES.iter() {
+        analysis(openapi, config, report);
+    }
+}
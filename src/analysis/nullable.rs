@@ -0,0 +1,100 @@
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+
+use crate::config::Config;
+use crate::passes::nullable::is_nullable;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const OPTIONAL_NULLABLE: Rule = Rule {
+    name: "optional-nullable",
+    severity: Severity::Warning,
+};
+
+/// Report nullable properties that aren't required, in any representation. Absent and `null`
+/// are then different states, which Rust clients model as `Option<Option<T>>`.
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas(openapi, config, &mut |origin, schema| {
+        let (properties, required) = match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(object)) => (&object.properties, &object.required),
+            SchemaKind::Any(any) => (&any.properties, &any.required),
+            _ => return,
+        };
+
+        for (name, property) in properties.iter() {
+            if required.contains(name) {
+                continue;
+            }
+
+            let property = match property {
+                ReferenceOr::Reference { reference } => {
+                    walk::resolve_schema(&components, reference)
+                }
+                ReferenceOr::Item(property) => Some(property.as_ref()),
+            };
+
+            if property.map(is_nullable).unwrap_or(false) {
+                report.add(
+                    &OPTIONAL_NULLABLE,
+                    origin.child(&["properties", name]).pointer,
+                    format!(
+                        "Property {} is nullable but not required, Rust clients get Option<Option<T>>",
+                        name
+                    ),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_nullable_properties_that_arent_required() {
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Nickname": {"type": "string", "x-nullable": true},
+                "User": {
+                    "type": "object",
+                    "required": ["id", "name"],
+                    "properties": {
+                        "id": {"type": "string"},
+                        "name": {"type": "string", "nullable": true},
+                        "email": {"type": "string"},
+                        "nickname": {"$ref": "#/components/schemas/Nickname"},
+                        "bio": {"oneOf": [{"type": "string"}, {"type": "null"}]}
+                    }
+                }
+            }}
+        }))
+        .unwrap();
+
+        let mut report = Report::default();
+        run(&openapi, &Config::default(), &mut report);
+
+        let locations: Vec<&str> = report
+            .findings()
+            .iter()
+            .map(|finding| finding.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                "#/components/schemas/User/properties/nickname",
+                "#/components/schemas/User/properties/bio"
+            ]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use openapiv3::AdditionalProperties;
+use openapiv3::Components;
+use openapiv3::ObjectType;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::passes::all_of;
+use crate::passes::union;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const AMBIGUOUS_UNION: Rule = Rule {
+    name: "ambiguous-union",
+    severity: Severity::Warning,
+};
+
+/// What a schema accepts, as far as telling union members apart goes
+enum Shape {
+    /// A primitive or array type, with the values its `enum` allows, empty for any
+    Value(&'static str, Vec<Value>),
+    Object(ObjectType),
+}
+
+/// Report `oneOf` unions where two members accept the same instance
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas(openapi, config, &mut |origin, schema| {
+        let SchemaKind::OneOf { one_of } = &schema.schema_kind else {
+            return;
+        };
+
+        // `{type: null}` members only make the union nullable
+        let shapes: Vec<Option<Shape>> = one_of
+            .iter()
+            .map(|member| {
+                if union::is_null(member) {
+                    None
+                } else {
+                    shape_of_slot(member, &components)
+                }
+            })
+            .collect();
+
+        for (a, a_shape) in shapes.iter().enumerate() {
+            for (b, b_shape) in shapes.iter().enumerate().skip(a + 1) {
+                let (Some(a_shape), Some(b_shape)) = (a_shape, b_shape) else {
+                    continue;
+                };
+
+                if let Some(overlap) = overlap(a_shape, b_shape, &components) {
+                    report.add(
+                        &AMBIGUOUS_UNION,
+                        origin.pointer.clone(),
+                        format!(
+                            "oneOf members {} and {} both accept {}",
+                            describe_member(&one_of[a], a),
+                            describe_member(&one_of[b], b),
+                            overlap
+                        ),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Describe what both shapes accept, `None` when no instance can match both
+fn overlap(a: &Shape, b: &Shape, components: &Components) -> Option<String> {
+    match (a, b) {
+        (Shape::Value(a_type, a_values), Shape::Value(b_type, b_values)) => {
+            let same_type = a_type == b_type
+                || matches!(
+                    (*a_type, *b_type),
+                    ("integer", "number") | ("number", "integer")
+                );
+
+            if !same_type || disjoint(a_values, b_values) {
+                return None;
+            }
+
+            let values: Vec<String> = if a_values.is_empty() {
+                b_values.iter().map(Value::to_string).collect()
+            } else {
+                a_values
+                    .iter()
+                    .filter(|value| b_values.is_empty() || b_values.contains(value))
+                    .map(Value::to_string)
+                    .collect()
+            };
+
+            let typ = if *a_type == "number" { b_type } else { a_type };
+
+            Some(if values.is_empty() {
+                format!("any {}", typ)
+            } else {
+                format!("the {} values {}", typ, values.join(", "))
+            })
+        }
+        (Shape::Object(a), Shape::Object(b)) => {
+            let mut required: Vec<&String> = a.required.iter().collect();
+            required.extend(b.required.iter().filter(|name| !a.required.contains(name)));
+
+            for name in required.iter() {
+                let distinct = match (a.properties.get(*name), b.properties.get(*name)) {
+                    (Some(a_property), Some(b_property)) => {
+                        match (
+                            shape_of_property(a_property, components),
+                            shape_of_property(b_property, components),
+                        ) {
+                            (Some(a_shape), Some(b_shape)) => {
+                                overlap(&a_shape, &b_shape, components).is_none()
+                            }
+                            _ => false,
+                        }
+                    }
+                    (Some(_), None) => is_closed(b),
+                    (None, Some(_)) => is_closed(a),
+                    (None, None) => false,
+                };
+
+                if distinct {
+                    return None;
+                }
+            }
+
+            let names: Vec<&str> = required.iter().map(|name| name.as_str()).collect();
+            let open = if is_closed(a) || is_closed(b) {
+                ""
+            } else {
+                " while additionalProperties is open"
+            };
+
+            Some(if names.is_empty() {
+                format!("any object, nothing is required{}", open)
+            } else {
+                format!(
+                    "objects with {}, none of which tells them apart{}",
+                    names.join(", "),
+                    open
+                )
+            })
+        }
+        _ => None,
+    }
+}
+
+fn disjoint(a: &[Value], b: &[Value]) -> bool {
+    !a.is_empty() && !b.is_empty() && !a.iter().any(|value| b.contains(value))
+}
+
+fn is_closed(object: &ObjectType) -> bool {
+    matches!(
+        object.additional_properties,
+        Some(AdditionalProperties::Any(false))
+    )
+}
+
+fn shape_of_slot(slot: &ReferenceOr<Schema>, components: &Components) -> Option<Shape> {
+    match slot {
+        ReferenceOr::Reference { reference } => {
+            shape(walk::resolve_schema(components, reference)?, components)
+        }
+        ReferenceOr::Item(schema) => shape(schema, components),
+    }
+}
+
+fn shape_of_property(slot: &ReferenceOr<Box<Schema>>, components: &Components) -> Option<Shape> {
+    match slot {
+        ReferenceOr::Reference { reference } => {
+            shape(walk::resolve_schema(components, reference)?, components)
+        }
+        ReferenceOr::Item(schema) => shape(schema, components),
+    }
+}
+
+/// `None` for schemas too loose or too complex to compare, which are never reported
+fn shape(schema: &Schema, components: &Components) -> Option<Shape> {
+    let values = |enumeration: Value| match enumeration {
+        Value::Array(values) => values,
+        _ => Vec::new(),
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(string)) => Some(Shape::Value(
+            "string",
+            values(serde_json::to_value(&string.enumeration).ok()?),
+        )),
+        SchemaKind::Type(Type::Integer(integer)) => Some(Shape::Value(
+            "integer",
+            values(serde_json::to_value(&integer.enumeration).ok()?),
+        )),
+        SchemaKind::Type(Type::Number(number)) => Some(Shape::Value(
+            "number",
+            values(serde_json::to_value(&number.enumeration).ok()?),
+        )),
+        SchemaKind::Type(Type::Boolean(boolean)) => Some(Shape::Value(
+            "boolean",
+            values(serde_json::to_value(&boolean.enumeration).ok()?),
+        )),
+        SchemaKind::Type(Type::Array(_)) => Some(Shape::Value("array", Vec::new())),
+        _ => all_of::flattened(schema, components).map(Shape::Object),
+    }
+}
+
+fn describe_member(member: &ReferenceOr<Schema>, index: usize) -> String {
+    match member {
+        ReferenceOr::Reference { reference } => reference.clone(),
+        ReferenceOr::Item(_) => format!("#{}", index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(schemas: Value) -> Vec<(String, String)> {
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": schemas}
+        }))
+        .unwrap();
+
+        let mut report = Report::default();
+        run(&openapi, &Config::default(), &mut report);
+
+        report
+            .findings()
+            .iter()
+            .map(|finding| (finding.location.clone(), finding.message.clone()))
+            .collect()
+    }
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/schemas/{}", name)})
+    }
+
+    #[test]
+    fn reports_members_accepting_the_same_instance() {
+        let found = messages(json!({
+            "Square": {"type": "object", "required": ["side"], "properties": {"side": {"type": "number"}}},
+            "Circle": {"type": "object", "required": ["radius"], "properties": {"radius": {"type": "number"}}},
+            "Cat": {"type": "object", "required": ["kind"], "properties": {"kind": {"type": "string", "enum": ["cat"]}}},
+            "Dog": {"type": "object", "required": ["kind"], "properties": {"kind": {"type": "string", "enum": ["dog"]}}},
+            "Box": {"type": "object", "required": ["side"], "properties": {"side": {"type": "number"}}, "additionalProperties": false},
+            "Shape": {"oneOf": [reference("Square"), reference("Circle")]},
+            // a closed object rejects the property it doesn't declare
+            "Closed": {"oneOf": [reference("Box"), reference("Circle")]},
+            "Pet": {"oneOf": [reference("Cat"), reference("Dog")]},
+            "Id": {"oneOf": [{"type": "integer"}, {"type": "number"}]},
+            "Status": {"oneOf": [{"type": "string", "enum": ["a", "b"]}, {"type": "string", "enum": ["b", "c"]}]},
+            "Code": {"oneOf": [{"type": "string", "enum": ["a"]}, {"type": "string", "enum": ["b"]}]},
+            "MaybeId": {"oneOf": [{"type": "string"}, {"type": "null"}]}
+        }));
+
+        assert_eq!(
+            found,
+            [
+                (
+                    "#/components/schemas/Shape".to_string(),
+                    "oneOf members #/components/schemas/Square and #/components/schemas/Circle both accept objects with side, radius, none of which tells them apart while additionalProperties is open".to_string()
+                ),
+                (
+                    "#/components/schemas/Id".to_string(),
+                    "oneOf members #0 and #1 both accept any integer".to_string()
+                ),
+                (
+                    "#/components/schemas/Status".to_string(),
+                    "oneOf members #0 and #1 both accept the string values \"b\"".to_string()
+                ),
+            ]
+        );
+    }
+}
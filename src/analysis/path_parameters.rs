@@ -0,0 +1,185 @@
+use openapiv3::Components;
+use openapiv3::OpenAPI;
+use openapiv3::Parameter;
+use openapiv3::PathItem;
+use openapiv3::ReferenceOr;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const REPEATED_PATH_PARAMETER: Rule = Rule {
+    name: "repeated-path-parameter",
+    severity: Severity::Info,
+};
+
+pub const OVERRIDDEN_PATH_PARAMETER: Rule = Rule {
+    name: "overridden-path-parameter",
+    severity: Severity::Info,
+};
+
+/// Report parameters every operation of a path repeats, which could be path-level parameters,
+/// and operation parameters that repeat or override a path-level one, callbacks included.
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    // the visitors are written for mutable slots, so walk a copy
+    let mut document = openapi.clone();
+    let _ = walk::all_path_items_mut(&mut document, config, &mut |origin, item| {
+        for parameter in shared(item, &components) {
+            let Some((location, name)) = key(&components, &parameter) else {
+                continue;
+            };
+
+            report.add(
+                &REPEATED_PATH_PARAMETER,
+                origin.pointer.clone(),
+                format!(
+                    "Every operation defines {} parameter {} the same way, it can be a path-level parameter",
+                    location, name
+                ),
+            );
+        }
+
+        for (method, operation) in item.iter() {
+            for (index, parameter) in operation.parameters.iter().enumerate() {
+                let Some(parameter_key) = key(&components, parameter) else {
+                    continue;
+                };
+
+                let Some(path_parameter) = item
+                    .parameters
+                    .iter()
+                    .find(|candidate| key(&components, candidate).as_ref() == Some(&parameter_key))
+                else {
+                    continue;
+                };
+
+                let location = origin
+                    .operation(method, operation)
+                    .child(&["parameters", &index.to_string()])
+                    .pointer;
+                let (parameter_location, name) = parameter_key;
+
+                if same(parameter, path_parameter) {
+                    report.add(
+                        &REPEATED_PATH_PARAMETER,
+                        location,
+                        format!(
+                            "Repeats the path-level {} parameter {}",
+                            parameter_location, name
+                        ),
+                    );
+                } else {
+                    report.add(
+                        &OVERRIDDEN_PATH_PARAMETER,
+                        location,
+                        format!(
+                            "Overrides the path-level {} parameter {} with a different definition",
+                            parameter_location, name
+                        ),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    });
+}
+
+/// Parameters every operation of a path item defines identically, in the order the first
+/// operation lists them.
+///
+/// Needs at least two operations, and skips parameters the path item already defines.
+pub fn shared(item: &PathItem, components: &Components) -> Vec<ReferenceOr<Parameter>> {
+    let operations: Vec<_> = item.iter().map(|(_, operation)| operation).collect();
+    if operations.len() < 2 {
+        return Vec::new();
+    }
+
+    operations[0]
+        .parameters
+        .iter()
+        .filter(|parameter| {
+            let Some(parameter_key) = key(components, parameter) else {
+                return false;
+            };
+
+            operations[1..].iter().all(|operation| {
+                operation
+                    .parameters
+                    .iter()
+                    .any(|other| same(parameter, other))
+            }) && !item
+                .parameters
+                .iter()
+                .any(|existing| key(components, existing).as_ref() == Some(&parameter_key))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Location and name of a parameter, following references
+pub fn key(
+    components: &Components,
+    parameter: &ReferenceOr<Parameter>,
+) -> Option<(&'static str, String)> {
+    walk::resolve_parameter(components, parameter).map(walk::parameter_key)
+}
+
+/// Whether two parameter slots are written the same way
+pub fn same(a: &ReferenceOr<Parameter>, b: &ReferenceOr<Parameter>) -> bool {
+    matches!((canonical_json(a), canonical_json(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn id(description: &str) -> Value {
+        json!({"name": "id", "in": "path", "required": true, "description": description, "schema": {"type": "string"}})
+    }
+
+    #[test]
+    fn reports_repeated_and_overridden_parameters() {
+        let query = json!({"name": "q", "in": "query", "schema": {"type": "string"}});
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {"/users/{id}": {
+                "parameters": [id("user")],
+                "get": {"parameters": [id("user"), query], "responses": {}},
+                "put": {"parameters": [id("user to replace"), query], "responses": {}}
+            }}
+        }))
+        .unwrap();
+
+        let mut report = Report::default();
+        run(&openapi, &Config::default(), &mut report);
+
+        let findings: Vec<(&str, &str)> = report
+            .findings()
+            .iter()
+            .map(|finding| (finding.rule, finding.location.as_str()))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                ("repeated-path-parameter", "#/paths/~1users~1{id}"),
+                (
+                    "repeated-path-parameter",
+                    "#/paths/~1users~1{id}/get/parameters/0"
+                ),
+                (
+                    "overridden-path-parameter",
+                    "#/paths/~1users~1{id}/put/parameters/0"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use openapiv3::ObjectType;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::report::pointer;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::COMPONENT_SCHEMA;
+
+pub const SIMILAR_SCHEMAS: Rule = Rule {
+    name: "similar-schemas",
+    severity: Severity::Info,
+};
+
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+struct Candidate<'a> {
+    name: &'a str,
+    schema: &'a Schema,
+    object: &'a ObjectType,
+    canonical: String,
+    property_names: HashSet<&'a str>,
+}
+
+/// Report pairs of component object schemas that are nearly, but not exactly, identical
+pub fn run(openapi: &OpenAPI, config: &Config, report: &mut Report) {
+    let threshold = config.similarity_threshold.unwrap_or(DEFAULT_THRESHOLD);
+
+    let Some(components) = openapi.components.as_ref() else {
+        return;
+    };
+
+    let mut candidates: Vec<Candidate> = components
+        .schemas
+        .iter()
+        .filter_map(|(name, schema)| match schema {
+            ReferenceOr::Item(schema) => match &schema.schema_kind {
+                SchemaKind::Type(Type::Object(object)) if !object.properties.is_empty() => {
+                    Some(Candidate {
+                        name,
+                        schema,
+                        object,
+                        canonical: canonical_json(schema).unwrap_or_default(),
+                        property_names: object.properties.keys().map(String::as_str).collect(),
+                    })
+                }
+                _ => None,
+            },
+            ReferenceOr::Reference { .. } => None,
+        })
+        .collect();
+
+    // bucket by size, a pair can't score above the ratio of their property counts
+    candidates.sort_by_key(|candidate| candidate.property_names.len());
+
+    for (index, a) in candidates.iter().enumerate() {
+        for b in candidates[index + 1..].iter() {
+            let (smaller, larger) = (a.property_names.len(), b.property_names.len());
+            if (smaller as f64) < threshold * larger as f64 {
+                break;
+            }
+
+            // exact duplicates are the merge pass' business
+            if a.canonical == b.canonical {
+                continue;
+            }
+
+            let shared = a.property_names.intersection(&b.property_names).count();
+            let total = a.property_names.union(&b.property_names).count();
+            if (shared as f64) < threshold * total as f64 {
+                continue;
+            }
+
+            let (score, differences) = compare(a, b);
+
+            // schemas differing only in how they serialize aren't worth a finding
+            if score >= threshold && !differences.is_empty() {
+                report.add(
+                    &SIMILAR_SCHEMAS,
+                    pointer(&["components", COMPONENT_SCHEMA, a.name]),
+                    format!(
+                        "Schema {} is {:.0}% similar to {}, consider merging them or composing one from the other with allOf: {}",
+                        a.name,
+                        score * 100.0,
+                        b.name,
+                        differences.join("; ")
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Score in 0..=1 and the differences between two object schemas, property by property and in
+/// the keywords of the schemas themselves
+fn compare(a: &Candidate, b: &Candidate) -> (f64, Vec<String>) {
+    let mut names: Vec<&str> = a.object.properties.keys().map(String::as_str).collect();
+    names.extend(
+        b.object
+            .properties
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !a.property_names.contains(name)),
+    );
+
+    let mut score = 0.0;
+    let mut differences = Vec::new();
+
+    for name in names.iter() {
+        let (a_schema, b_schema) = match (
+            a.object.properties.get(*name),
+            b.object.properties.get(*name),
+        ) {
+            (Some(a_schema), Some(b_schema)) => (a_schema, b_schema),
+            (Some(_), None) => {
+                differences.push(format!("{} only in {}", name, a.name));
+                continue;
+            }
+            (None, Some(_)) => {
+                differences.push(format!("{} only in {}", name, b.name));
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        let a_value = serde_json::to_value(a_schema).unwrap_or_default();
+        let b_value = serde_json::to_value(b_schema).unwrap_or_default();
+
+        let mut property_score = if a_value == b_value {
+            1.0
+        } else if a_value.get("type").is_some() && a_value.get("type") == b_value.get("type") {
+            for keyword in keyword_differences(&a_value, &b_value) {
+                differences.push(format!(
+                    "{} of {} differs ({} vs {})",
+                    keyword,
+                    name,
+                    a_value.get(&keyword).unwrap_or(&Value::Null),
+                    b_value.get(&keyword).unwrap_or(&Value::Null)
+                ));
+            }
+            0.5
+        } else {
+            differences.push(format!("{} has a different schema", name));
+            0.0
+        };
+
+        let a_required = a.object.required.iter().any(|required| required == name);
+        let b_required = b.object.required.iter().any(|required| required == name);
+        if a_required != b_required {
+            differences.push(format!(
+                "{} is required only in {}",
+                name,
+                if a_required { a.name } else { b.name }
+            ));
+            property_score *= 0.75;
+        }
+
+        score += property_score;
+    }
+
+    // every other keyword, such as `description` or `additionalProperties`, counts like a
+    // property that differs entirely
+    let a_value = serde_json::to_value(a.schema).unwrap_or_default();
+    let b_value = serde_json::to_value(b.schema).unwrap_or_default();
+    let keywords: Vec<String> = keyword_differences(&a_value, &b_value)
+        .into_iter()
+        .filter(|keyword| keyword != "properties" && keyword != "required")
+        .collect();
+
+    for keyword in keywords.iter() {
+        differences.push(format!(
+            "{} differs ({} vs {})",
+            keyword,
+            a_value.get(keyword).unwrap_or(&Value::Null),
+            b_value.get(keyword).unwrap_or(&Value::Null)
+        ));
+    }
+
+    (score / (names.len() + keywords.len()) as f64, differences)
+}
+
+/// Keywords whose values differ between two schemas
+fn keyword_differences(a: &Value, b: &Value) -> Vec<String> {
+    let (Some(a), Some(b)) = (a.as_object(), b.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut keywords: Vec<String> = a
+        .iter()
+        .filter(|(keyword, value)| b.get(*keyword) != Some(*value))
+        .map(|(keyword, _)| keyword.clone())
+        .collect();
+
+    keywords.extend(
+        b.keys()
+            .filter(|keyword| !a.contains_key(*keyword))
+            .cloned(),
+    );
+
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(schemas: Value, threshold: f64) -> Vec<String> {
+        let openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": schemas}
+        }))
+        .unwrap();
+        let config = Config {
+            similarity_threshold: Some(threshold),
+            ..Default::default()
+        };
+
+        let mut report = Report::default();
+        run(&openapi, &config, &mut report);
+
+        report
+            .findings()
+            .iter()
+            .map(|finding| finding.message.clone())
+            .collect()
+    }
+
+    fn properties() -> Value {
+        json!({
+            "id": {"type": "string"},
+            "name": {"type": "string"},
+            "email": {"type": "string"},
+            "age": {"type": "integer"}
+        })
+    }
+
+    #[test]
+    fn reports_property_differences() {
+        let mut other = properties();
+        other["age"] = json!({"type": "integer", "minimum": 0});
+
+        assert_eq!(
+            messages(
+                json!({
+                    "User": {"type": "object", "properties": properties(), "required": ["id"]},
+                    "Account": {"type": "object", "properties": other, "required": ["id"]},
+                    "Tag": {"type": "object", "properties": {"label": {"type": "string"}}}
+                }),
+                0.8
+            ),
+            ["Schema User is 88% similar to Account, consider merging them or composing one from the other with allOf: minimum of age differs (null vs 0)"]
+        );
+    }
+
+    #[test]
+    fn reports_differences_outside_properties() {
+        assert_eq!(
+            messages(
+                json!({
+                    "User": {"type": "object", "properties": properties(), "description": "A user"},
+                    "Account": {"type": "object", "properties": properties(), "additionalProperties": false}
+                }),
+                0.6
+            ),
+            ["Schema User is 67% similar to Account, consider merging them or composing one from the other with allOf: description differs (\"A user\" vs null); additionalProperties differs (null vs false)"]
+        );
+    }
+
+    #[test]
+    fn leaves_exact_duplicates_to_merge() {
+        assert!(messages(
+            json!({
+                "User": {"type": "object", "properties": properties()},
+                "Account": {"type": "object", "properties": properties()}
+            }),
+            0.5
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::naming::Case;
+use crate::passes::alias::Aliases;
+use crate::passes::canonical::ComponentOrder;
+use crate::passes::nullable::Nullable;
+use crate::report::Severity;
+
+pub const CONFIG_FILE_NAME: &str = ".openapi-normalizer.toml";
+
+/// Normalization options, loaded from `.openapi-normalizer.toml` and overridden by CLI flags.
+///
+/// Every field mirrors a CLI flag of the same (kebab-case) name.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Write the normalized schema to this file
+    pub output: Option<PathBuf>,
+    /// Normalization passes to run, `None` runs the default set
+    pub passes: Option<Vec<String>>,
+    /// Paths left untouched by passes and findings, `*` matches any characters
+    pub exclude_paths: Vec<String>,
+    /// Severity overrides keyed by rule name
+    pub severity: HashMap<String, Severity>,
+    /// Name template for schemas hoisted out of operations
+    pub hoisted_name: Option<String>,
+    /// Name template for the survivor of merged duplicates
+    pub merged_name: Option<String>,
+    /// Name template for enums extracted from inline copies
+    pub enum_name: Option<String>,
+    /// Case the rendered names are converted to
+    pub name_case: Option<Case>,
+    /// Keep generated names stable across runs by recording them in this file
+    pub lockfile: Option<PathBuf>,
+    /// Minimum score, between 0 and 1, for two schemas to be reported as similar
+    pub similarity_threshold: Option<f64>,
+    /// How the nullable pass writes nullable schemas
+    pub nullable: Option<Nullable>,
+    /// How the canonicalize pass orders `components.schemas`
+    pub component_order: Option<ComponentOrder>,
+    /// What the collapse-aliases pass does with alias components
+    pub aliases: Option<Aliases>,
+}
+
+impl Config {
+    /// Read a config file, resolving relative paths against its directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!(format!("Cant read config {:?}: {}", path, e)))?;
+
+        let mut config: Config = toml::from_str(&data)
+            .map_err(|e| anyhow!(format!("Invalid config {:?}: {}", path, e)))?;
+
+        if let Some(threshold) = config.similarity_threshold {
+            check_similarity_threshold(threshold)
+                .map_err(|e| anyhow!(format!("Invalid config {:?}: {}", path, e)))?;
+        }
+
+        if let Some(dir) = path.parent() {
+            config.output = config.output.map(|output| dir.join(output));
+            config.lockfile = config.lockfile.map(|lockfile| dir.join(lockfile));
+        }
+
+        Ok(config)
+    }
+
+    /// Find the nearest config file in the schema's directory or any directory above it
+    pub fn discover(schema: &Path) -> Option<PathBuf> {
+        schema
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Apply `other` on top of `self`, any option set in `other` wins
+    pub fn merge(mut self, other: Config) -> Self {
+        if other.output.is_some() {
+            self.output = other.output;
+        }
+
+        if other.passes.is_some() {
+            self.passes = other.passes;
+        }
+
+        if !other.exclude_paths.is_empty() {
+            self.exclude_paths = other.exclude_paths;
+        }
+
+        self.severity.extend(other.severity);
+
+        if other.hoisted_name.is_some() {
+            self.hoisted_name = other.hoisted_name;
+        }
+
+        if other.merged_name.is_some() {
+            self.merged_name = other.merged_name;
+        }
+
+        if other.enum_name.is_some() {
+            self.enum_name = other.enum_name;
+        }
+
+        if other.name_case.is_some() {
+            self.name_case = other.name_case;
+        }
+
+        if other.lockfile.is_some() {
+            self.lockfile = other.lockfile;
+        }
+
+        if other.similarity_threshold.is_some() {
+            self.similarity_threshold = other.similarity_threshold;
+        }
+
+        if other.nullable.is_some() {
+            self.nullable = other.nullable;
+        }
+
+        if other.component_order.is_some() {
+            self.component_order = other.component_order;
+        }
+
+        if other.aliases.is_some() {
+            self.aliases = other.aliases;
+        }
+
+        self
+    }
+
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.exclude_paths
+            .iter()
+            .any(|pattern| glob_match(pattern, path))
+    }
+}
+
+/// A similarity threshold is a score, between 0 and 1
+pub fn check_similarity_threshold(threshold: f64) -> Result<f64> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(anyhow!(format!(
+            "similarity-threshold must be between 0 and 1, got {}",
+            threshold
+        )));
+    }
+
+    Ok(threshold)
+}
+
+/// Match `text` against `pattern` where `*` stands for any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+
+    let Some((last, middle)) = parts.split_last() else {
+        // no wildcard at all
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_without_wildcard_is_exact() {
+        assert!(glob_match("/users", "/users"));
+        assert!(!glob_match("/users", "/users/1"));
+        assert!(!glob_match("/users", "/user"));
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("/internal/*", "/internal/health"));
+        assert!(glob_match("/internal/*", "/internal/"));
+        assert!(!glob_match("/internal/*", "/internals"));
+        assert!(glob_match("*/admin", "/v1/admin"));
+        assert!(glob_match("/v*/users/*/orders", "/v2/users/{id}/orders"));
+        assert!(!glob_match("/v*/users/*/orders", "/v2/users/{id}"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn glob_match_doesnt_reuse_the_prefix_for_the_suffix() {
+        assert!(!glob_match("/a*a", "/a"));
+        assert!(glob_match("/a*a", "/aa"));
+    }
+
+    #[test]
+    fn similarity_threshold_is_a_score() {
+        assert!(check_similarity_threshold(0.0).is_ok());
+        assert!(check_similarity_threshold(1.0).is_ok());
+        assert!(check_similarity_threshold(1.5).is_err());
+        assert!(check_similarity_threshold(-0.1).is_err());
+        assert!(check_similarity_threshold(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn is_excluded_matches_any_pattern() {
+        let config = Config {
+            exclude_paths: vec!["/internal/*".to_string(), "/health".to_string()],
+            ..Default::default()
+        };
+
+        assert!(config.is_excluded("/health"));
+        assert!(config.is_excluded("/internal/metrics"));
+        assert!(!config.is_excluded("/users"));
+    }
+}
@@ -0,0 +1,142 @@
+use serde_json::Value;
+use std::fmt;
+
+use crate::report::pointer;
+
+#[derive(Debug)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    Changed(String),
+    /// The same keys, in a different order
+    Reordered(String),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(location) => write!(f, "+ {}", location),
+            Change::Removed(location) => write!(f, "- {}", location),
+            Change::Changed(location) => write!(f, "~ {}", location),
+            Change::Reordered(location) => write!(f, "~ {} (key order)", location),
+        }
+    }
+}
+
+/// Differences between two documents, reported at the outermost node that differs, key order
+/// included
+pub fn diff(before: &Value, after: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut tokens = Vec::new();
+
+    diff_value(before, after, &mut tokens, &mut changes);
+
+    changes
+}
+
+fn diff_value(before: &Value, after: &Value, tokens: &mut Vec<String>, changes: &mut Vec<Change>) {
+    let location =
+        |tokens: &Vec<String>| pointer(&tokens.iter().map(String::as_str).collect::<Vec<&str>>());
+
+    match (before, after) {
+        // an inline schema replaced by a reference, or the other way around
+        (Value::Object(before), Value::Object(after))
+            if before.contains_key("$ref") != after.contains_key("$ref") =>
+        {
+            changes.push(Change::Changed(location(tokens)));
+        }
+        (Value::Object(before), Value::Object(after)) => {
+            let kept_before = before.keys().filter(|key| after.contains_key(*key));
+            let kept_after = after.keys().filter(|key| before.contains_key(*key));
+            if !kept_before.eq(kept_after) {
+                changes.push(Change::Reordered(location(tokens)));
+            }
+
+            for (key, before_value) in before.iter() {
+                tokens.push(key.clone());
+                match after.get(key) {
+                    Some(after_value) => diff_value(before_value, after_value, tokens, changes),
+                    None => changes.push(Change::Removed(location(tokens))),
+                }
+                tokens.pop();
+            }
+
+            for key in after.keys().filter(|key| !before.contains_key(*key)) {
+                tokens.push(key.clone());
+                changes.push(Change::Added(location(tokens)));
+                tokens.pop();
+            }
+        }
+        (Value::Array(before), Value::Array(after)) => {
+            for index in 0..before.len().max(after.len()) {
+                tokens.push(index.to_string());
+                match (before.get(index), after.get(index)) {
+                    (Some(before_value), Some(after_value)) => {
+                        diff_value(before_value, after_value, tokens, changes)
+                    }
+                    (Some(_), None) => changes.push(Change::Removed(location(tokens))),
+                    (None, Some(_)) => changes.push(Change::Added(location(tokens))),
+                    (None, None) => {}
+                }
+                tokens.pop();
+            }
+        }
+        (before, after) => {
+            if before != after {
+                changes.push(Change::Changed(location(tokens)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn changes(before: Value, after: Value) -> Vec<String> {
+        diff(&before, &after)
+            .iter()
+            .map(Change::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn equal_documents_have_no_changes() {
+        let document = json!({"paths": {"/a": {"get": {}}}, "tags": ["x"]});
+
+        assert!(changes(document.clone(), document).is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_values() {
+        assert_eq!(
+            changes(
+                json!({"a": 1, "b": {"c": true}, "d": [1, 2]}),
+                json!({"a": 2, "b": {}, "d": [1], "e/f": null})
+            ),
+            ["~ #/a", "- #/b/c", "- #/d/1", "+ #/e~1f"]
+        );
+    }
+
+    #[test]
+    fn reports_key_order() {
+        assert_eq!(
+            changes(json!({"a": 1, "b": 2}), json!({"b": 2, "a": 1})),
+            ["~ # (key order)"]
+        );
+        // added keys alone don't count as reordering
+        assert_eq!(changes(json!({"b": 2}), json!({"a": 1, "b": 2})), ["+ #/a"]);
+    }
+
+    #[test]
+    fn reports_references_at_the_replaced_schema() {
+        assert_eq!(
+            changes(
+                json!({"schema": {"type": "object", "properties": {}}}),
+                json!({"schema": {"$ref": "#/components/schemas/A"}})
+            ),
+            ["~ #/schema"]
+        );
+    }
+}
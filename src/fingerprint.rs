@@ -0,0 +1,38 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Key order independent JSON rendering, equal for structurally identical values
+pub fn canonical_json(value: &impl Serialize) -> Result<String> {
+    Ok(sorted(serde_json::to_value(value)?).to_string())
+}
+
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sorted(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
+
+/// Stable 64 bit FNV-1a hash of the canonical rendering, as hex
+pub fn hash(value: &impl Serialize) -> Result<String> {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in canonical_json(value)?.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    Ok(format!("{:016x}", hash))
+}
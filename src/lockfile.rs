@@ -0,0 +1,82 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+/// A name chosen for a hoisted or merged schema
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Hash of the schema content, see `fingerprint::hash`
+    pub hash: String,
+    /// JSON pointer the schema was found at
+    pub origin: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct LockData {
+    names: Vec<Entry>,
+}
+
+/// Names from previous runs, so generated components keep their names when the spec changes
+#[derive(Debug, Default)]
+pub struct Lockfile {
+    previous: Vec<Entry>,
+    current: Vec<Entry>,
+}
+
+impl Lockfile {
+    /// Read a lockfile, a missing file is an empty lock
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!(format!("Cant read lockfile {:?}: {}", path, e)))?;
+
+        let data: LockData = serde_json::from_str(&data)
+            .map_err(|e| anyhow!(format!("Invalid lockfile {:?}: {}", path, e)))?;
+
+        Ok(Self {
+            previous: data.names,
+            current: Vec::new(),
+        })
+    }
+
+    /// Locked name for a schema. Same content at the same place wins, then same content
+    /// anywhere, then whatever was at the same place before it changed.
+    pub fn find(&self, hash: &str, origin: &str) -> Option<&str> {
+        self.previous
+            .iter()
+            .find(|entry| entry.hash == hash && entry.origin == origin)
+            .or_else(|| self.previous.iter().find(|entry| entry.hash == hash))
+            .or_else(|| self.previous.iter().find(|entry| entry.origin == origin))
+            .map(|entry| entry.name.as_str())
+    }
+
+    /// Whether a previous run gave the name to some schema, which new schemas mustn't take
+    pub fn is_locked(&self, name: &str) -> bool {
+        self.previous.iter().any(|entry| entry.name == name)
+    }
+
+    pub fn record(&mut self, hash: String, origin: String, name: String) {
+        self.current.retain(|entry| entry.origin != origin);
+        self.current.push(Entry { hash, origin, name });
+    }
+
+    /// Write the names chosen in this run
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut names = self.current.clone();
+        names.sort_by(|a, b| a.origin.cmp(&b.origin));
+
+        let mut data = serde_json::to_string_pretty(&LockData { names })?;
+        data.push('\n');
+
+        std::fs::write(path, data)
+            .map_err(|e| anyhow!(format!("Cant write lockfile {:?}: {}", path, e)))?;
+
+        Ok(())
+    }
+}
@@ -1,19 +1,54 @@
+mod analysis;
+mod config;
+mod diff;
+mod fingerprint;
+mod lockfile;
+mod naming;
+mod passes;
+mod report;
+mod walk;
+
+use analysis::equivalent_paths::template_parts;
 use anyhow::anyhow;
 use anyhow::Result;
 use clap::Parser;
+use config::Config;
+use indexmap::IndexMap;
+use lockfile::Lockfile;
+use naming::Case;
+use naming::Naming;
 use openapiv3::OpenAPI;
+use openapiv3::PathItem;
 use openapiv3::ReferenceOr;
 use openapiv3::Schema;
-use openapiv3::StatusCode;
-use serde_json;
-use std::collections::HashMap;
+use passes::alias::Aliases;
+use passes::canonical::ComponentOrder;
+use passes::nullable::Nullable;
+use passes::Pass;
+use report::pointer;
+use report::Report;
+use report::Severity;
+use serde_json::Value;
 use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
 pub const COMPONENT_SCHEMA: &str = "schemas";
 pub const COMPONENT_PARAM: &str = "parameters";
 pub const COMPONENT_RESPONSE: &str = "responses";
+pub const COMPONENT_HEADER: &str = "headers";
+pub const COMPONENT_REQUEST_BODY: &str = "requestBodies";
+pub const COMPONENT_EXAMPLE: &str = "examples";
+pub const COMPONENT_LINK: &str = "links";
+pub const COMPONENT_CALLBACK: &str = "callbacks";
+pub const COMPONENT_SECURITY_SCHEME: &str = "securitySchemes";
+
+/// Exit code when a finding is reported with error severity
+pub const EXIT_FINDINGS: u8 = 2;
+/// Exit code when `--check` finds the schema is not normalized
+pub const EXIT_CHECK_FAILED: u8 = 3;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -21,10 +56,107 @@ struct Args {
     /// Schema file
     #[arg(short, long)]
     schema: String,
+
+    /// Config file, defaults to the nearest .openapi-normalizer.toml above the schema
+    #[arg(short, long)]
+    config: Option<String>,
+
+    /// Write the normalized schema to this file
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Normalize in memory and fail if the schema would change, without writing anything
+    #[arg(long)]
+    check: bool,
+
+    /// Normalization pass to run, can be repeated
+    #[arg(long = "pass")]
+    passes: Vec<String>,
+
+    /// Path to leave untouched, `*` matches any characters, can be repeated
+    #[arg(long = "exclude-path")]
+    exclude_paths: Vec<String>,
+
+    /// Severity override as rule=level, can be repeated
+    #[arg(long = "severity", value_parser = parse_severity_override)]
+    severity: Vec<(String, Severity)>,
+
+    /// Name template for hoisted schemas, e.g. {operationId}{location}{status}{paramName}
+    #[arg(long)]
+    hoisted_name: Option<String>,
+
+    /// Name template for merged duplicates, defaults to the first duplicate's name
+    #[arg(long)]
+    merged_name: Option<String>,
+
+    /// Name template for extracted enums, e.g. {property}{paramName}
+    #[arg(long)]
+    enum_name: Option<String>,
+
+    /// Case of generated names: pascal, snake or preserve
+    #[arg(long)]
+    name_case: Option<Case>,
+
+    /// Record generated names in this file and reuse them on later runs
+    #[arg(long)]
+    lockfile: Option<String>,
+
+    /// Minimum score, between 0 and 1, for two schemas to be reported as similar
+    #[arg(long, value_parser = parse_similarity_threshold)]
+    similarity_threshold: Option<f64>,
+
+    /// Nullable representation written by the nullable pass: keyword, union or extension
+    #[arg(long)]
+    nullable: Option<Nullable>,
+
+    /// Order of components.schemas written by the canonicalize pass: alphabetical or dependency
+    #[arg(long)]
+    component_order: Option<ComponentOrder>,
+
+    /// What the collapse-aliases pass does with alias components: remove or keep
+    #[arg(long)]
+    aliases: Option<Aliases>,
+}
+
+impl Args {
+    /// The options given on the command line, to be merged over the config file
+    fn to_config(&self) -> Config {
+        Config {
+            output: self.output.as_ref().map(PathBuf::from),
+            passes: (!self.passes.is_empty()).then(|| self.passes.clone()),
+            exclude_paths: self.exclude_paths.clone(),
+            severity: self.severity.iter().cloned().collect(),
+            hoisted_name: self.hoisted_name.clone(),
+            merged_name: self.merged_name.clone(),
+            enum_name: self.enum_name.clone(),
+            name_case: self.name_case,
+            lockfile: self.lockfile.as_ref().map(PathBuf::from),
+            similarity_threshold: self.similarity_threshold,
+            nullable: self.nullable,
+            component_order: self.component_order,
+            aliases: self.aliases,
+        }
+    }
+}
+
+fn parse_similarity_threshold(value: &str) -> Result<f64> {
+    let threshold = value
+        .parse()
+        .map_err(|_| anyhow!(format!("Expected a number, got {}", value)))?;
+
+    config::check_similarity_threshold(threshold)
+}
+
+fn parse_severity_override(value: &str) -> Result<(String, Severity)> {
+    let (rule, level) = value
+        .split_once('=')
+        .ok_or_else(|| anyhow!(format!("Expected rule=level, got {}", value)))?;
+
+    Ok((rule.to_string(), level.parse()?))
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let args = Args::parse();
 
     let path = Path::new(&args.schema).canonicalize()?;
@@ -33,26 +165,161 @@ async fn main() -> Result<()> {
         return Err(anyhow!(format!("Cant read file {:?}", path)));
     }
 
+    let config_path = match &args.config {
+        Some(config_path) => Some(PathBuf::from(config_path)),
+        None => Config::discover(&path),
+    };
+
+    let mut config = Config::default();
+    if let Some(config_path) = config_path {
+        println!("Using config {:?}", config_path);
+        config = Config::load(&config_path)?;
+    }
+    let config = config.merge(args.to_config());
+
+    let mut report = Report::new(&config)?;
+    let selected_passes = passes::selected(&config)?;
+    let mut naming = Naming::from_config(&config)?;
+    if let Some(lockfile) = &config.lockfile {
+        naming.lock = Lockfile::load(lockfile)?;
+    }
+
     let mut data = String::new();
     File::open(path).await?.read_to_string(&mut data).await?;
 
-    let openapi: OpenAPI = serde_json::from_str(&data).expect("Could not deserialize input");
+    let mut openapi: OpenAPI = serde_json::from_str(&data).expect("Could not deserialize input");
+
+    // the parser only keeps the 3.0 fields, webhooks ride along as an extension until rendering
+    let raw: Value = serde_json::from_str(&data)?;
+    if let Some(webhooks) = raw.get("webhooks") {
+        serde_json::from_value::<IndexMap<String, ReferenceOr<PathItem>>>(webhooks.clone())
+            .map_err(|e| anyhow!(format!("Invalid webhooks: {}", e)))?;
+        openapi
+            .extensions
+            .insert(walk::WEBHOOKS_EXTENSION.to_string(), webhooks.clone());
+    }
     // println!("{:?}", openapi);
 
-    let mut complex_component_params = HashMap::new();
-    let mut simple_component_params = HashMap::new();
-    let mut referenced_component_params: Vec<String> = Vec::new();
-    let mut redundant_simple_component_params: Vec<String> = Vec::new();
+    scan(&openapi, &mut report);
+    analysis::run(&openapi, &config, &mut report);
+
+    let mut check_failed = false;
+
+    if args.check {
+        println!("Checking");
+
+        // compare the file with what `--output` would write, key order included
+        let before: Value = serde_json::from_str(&data)?;
+        passes::run(
+            &selected_passes,
+            &mut openapi,
+            &config,
+            &mut naming,
+            &mut report,
+        )?;
+        let after = render(&selected_passes, &openapi)?;
+
+        let changes = diff::diff(&before, &after);
+
+        let mut normalized = serde_json::to_string_pretty(&after)?;
+        normalized.push('\n');
+
+        if changes.is_empty() && normalized == data {
+            println!("Schema is already normalized");
+        } else if changes.is_empty() {
+            println!("Normalizing would only change the formatting");
+            check_failed = true;
+        } else {
+            println!("Normalizing would change {} locations", changes.len());
+            for change in changes.iter() {
+                println!("{}", change);
+            }
+            check_failed = true;
+        }
+
+        println!();
+    } else if let Some(output) = &config.output {
+        println!("Normalizing");
+
+        passes::run(
+            &selected_passes,
+            &mut openapi,
+            &config,
+            &mut naming,
+            &mut report,
+        )?;
+
+        let document = render(&selected_passes, &openapi)?;
+
+        let mut normalized = serde_json::to_string_pretty(&document)?;
+        normalized.push('\n');
+        tokio::fs::write(output, normalized).await?;
+
+        println!("Wrote {:?}", output);
+
+        if let Some(lockfile) = &config.lockfile {
+            naming.lock.save(lockfile)?;
+            println!("Wrote {:?}", lockfile);
+        }
+        println!();
+    }
+
+    println!("Report");
 
-    let mut complex_component_schemas = HashMap::new();
-    let mut simple_component_schemas = HashMap::new();
-    let mut referenced_component_schemas: Vec<String> = Vec::new();
-    let mut redundant_simple_component_schemas: Vec<String> = Vec::new();
+    report.print();
 
-    let mut complex_component_responses = HashMap::new();
-    let mut simple_component_responses = HashMap::new();
-    let mut referenced_component_responses: Vec<String> = Vec::new();
-    let mut redundant_simple_component_responses: Vec<String> = Vec::new();
+    if check_failed {
+        return Ok(ExitCode::from(EXIT_CHECK_FAILED));
+    }
+
+    if report.has_errors() {
+        return Ok(ExitCode::from(EXIT_FINDINGS));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The document as `--output` writes it, webhooks back in place after `paths`
+fn render(selected_passes: &[&Pass], openapi: &OpenAPI) -> Result<Value> {
+    let Value::Object(map) = serde_json::to_value(openapi)? else {
+        return Err(anyhow!("Document is not an object"));
+    };
+
+    let mut webhooks = map.get(walk::WEBHOOKS_EXTENSION).cloned();
+    let mut document = serde_json::Map::new();
+    for (key, value) in map {
+        if key == walk::WEBHOOKS_EXTENSION {
+            continue;
+        }
+
+        let after_paths = key == "paths";
+        document.insert(key, value);
+        if after_paths {
+            if let Some(webhooks) = webhooks.take() {
+                document.insert("webhooks".to_string(), webhooks);
+            }
+        }
+    }
+    if let Some(webhooks) = webhooks {
+        document.insert("webhooks".to_string(), webhooks);
+    }
+
+    let mut document = Value::Object(document);
+    passes::render(selected_passes, &mut document);
+
+    Ok(document)
+}
+
+/// Walk components and paths, printing what is found and reporting unused components
+pub fn scan(openapi: &OpenAPI, report: &mut Report) {
+    let mut complex_component_params = IndexMap::new();
+    let mut simple_component_params = IndexMap::new();
+
+    let mut complex_component_schemas = IndexMap::new();
+    let mut simple_component_schemas = IndexMap::new();
+
+    let mut complex_component_responses = IndexMap::new();
+    let mut simple_component_responses = IndexMap::new();
 
     println!("Collecting schema information");
 
@@ -60,11 +327,7 @@ async fn main() -> Result<()> {
         for (name, schema) in components.schemas.iter() {
             match schema {
                 ReferenceOr::Reference { reference } => {
-                    // reference
-                    println!(
-                        "Thats weird. Found schema reference {} => {}",
-                        name, reference
-                    );
+                    println!("Found schema alias {} => {}", name, reference);
                 }
                 ReferenceOr::Item(schema) => {
                     if is_complex(schema) {
@@ -79,11 +342,7 @@ async fn main() -> Result<()> {
         for (name, param) in components.parameters.iter() {
             match param {
                 ReferenceOr::Reference { reference } => {
-                    // reference
-                    println!(
-                        "Thats weird. Found param reference {} => {}",
-                        name, reference
-                    );
+                    println!("Found param alias {} => {}", name, reference);
                 }
                 ReferenceOr::Item(param) => match &param.parameter_data_ref().format {
                     openapiv3::ParameterSchemaOrContent::Schema(schema) => {
@@ -124,10 +383,7 @@ async fn main() -> Result<()> {
         for (name, response) in components.responses.iter() {
             match response {
                 ReferenceOr::Reference { reference } => {
-                    println!(
-                        "Thats weird. Found response reference {} => {}",
-                        name, reference
-                    );
+                    println!("Found response alias {} => {}", name, reference);
                 }
                 ReferenceOr::Item(response) => {
                     for (header_name, header) in response.headers.iter() {
@@ -246,23 +502,54 @@ async fn main() -> Result<()> {
 
     println!("Parsing paths information");
 
+    let components = openapi.components.clone().unwrap_or_default();
+
     for (name, path) in openapi.paths.iter() {
         println!("Scanning path {}", name);
         if let Some(path) = path.as_item() {
-            for (_op_name, operation) in path.iter() {
-                for param in operation.parameters.iter() {
+            check_declared_path_parameters(
+                &components,
+                name,
+                &path.parameters,
+                &["paths", name],
+                report,
+            );
+
+            for (op_name, operation) in path.iter() {
+                check_declared_path_parameters(
+                    &components,
+                    name,
+                    &operation.parameters,
+                    &["paths", name, op_name],
+                    report,
+                );
+
+                for variable in template_parts(name).1 {
+                    let declared = path
+                        .parameters
+                        .iter()
+                        .chain(operation.parameters.iter())
+                        .filter_map(|param| walk::resolve_parameter(&components, param))
+                        .any(|param| walk::parameter_key(param) == ("path", variable.to_string()));
+
+                    if !declared {
+                        report.add(
+                            &report::MISSING_PATH_PARAMETER,
+                            pointer(&["paths", name, op_name]),
+                            format!(
+                                "Path variable {{{}}} has no `in: path` parameter, on the operation or the path",
+                                variable
+                            ),
+                        );
+                    }
+                }
+
+                // path-level parameters apply to every operation
+                for param in path.parameters.iter().chain(operation.parameters.iter()) {
                     match param {
                         ReferenceOr::Reference { reference } => {
                             let ref_data = parse_reference(reference);
 
-                            if ref_data.1 == COMPONENT_PARAM {
-                                referenced_component_params.push(ref_data.0.to_string());
-                            } else if ref_data.1 == COMPONENT_SCHEMA {
-                                referenced_component_schemas.push(ref_data.0.to_string());
-                            } else {
-                                // unhandled component type
-                            }
-
                             println!("Param reference name {} of type {}", ref_data.0, ref_data.1);
                         }
                         ReferenceOr::Item(param) => {
@@ -270,20 +557,8 @@ async fn main() -> Result<()> {
                                 openapiv3::ParameterSchemaOrContent::Schema(schema) => {
                                     match schema {
                                         ReferenceOr::Reference { reference } => {
-                                            // count references to find reduntant component schemas
-
                                             let ref_data = parse_reference(reference);
 
-                                            if ref_data.1 == COMPONENT_PARAM {
-                                                referenced_component_params
-                                                    .push(ref_data.0.to_string());
-                                            } else if ref_data.1 == COMPONENT_SCHEMA {
-                                                referenced_component_schemas
-                                                    .push(ref_data.0.to_string());
-                                            } else {
-                                                // unhandled component type
-                                            }
-
                                             println!(
                                                 "Param {} reference name {} of type {}",
                                                 param.parameter_data_ref().name,
@@ -316,16 +591,6 @@ async fn main() -> Result<()> {
                                                 ReferenceOr::Reference { reference } => {
                                                     let ref_data = parse_reference(reference);
 
-                                                    if ref_data.1 == COMPONENT_PARAM {
-                                                        referenced_component_params
-                                                            .push(ref_data.0.to_string());
-                                                    } else if ref_data.1 == COMPONENT_SCHEMA {
-                                                        referenced_component_schemas
-                                                            .push(ref_data.0.to_string());
-                                                    } else {
-                                                        // unhandled component type
-                                                    }
-
                                                     println!(
                                                         "Param reference name {} of type {}",
                                                         ref_data.0, ref_data.1
@@ -353,26 +618,25 @@ async fn main() -> Result<()> {
                     }
                 }
 
-                for (resp_code, resp_obj) in operation.responses.responses.iter().chain(
-                    operation
-                        .responses
-                        .default
-                        .iter()
-                        .map(|def_resp| (&StatusCode::Code(200), def_resp)),
-                ) {
+                // `default` is the catch-all, not any particular status
+                for (resp_code, resp_obj) in operation
+                    .responses
+                    .responses
+                    .iter()
+                    .map(|(status, response)| (status.to_string(), response))
+                    .chain(
+                        operation
+                            .responses
+                            .default
+                            .iter()
+                            .map(|response| ("default".to_string(), response)),
+                    )
+                {
                     match resp_obj {
                         ReferenceOr::Reference { reference } => {
                             // the whole response object is a reference
                             let ref_data = parse_reference(reference);
 
-                            if ref_data.1 == COMPONENT_PARAM {
-                                referenced_component_params.push(ref_data.0.to_string());
-                            } else if ref_data.1 == COMPONENT_SCHEMA {
-                                referenced_component_schemas.push(ref_data.0.to_string());
-                            } else {
-                                // unhandled component type
-                            }
-
                             println!(
                                 "Response reference name {} of type {}",
                                 ref_data.0, ref_data.1
@@ -386,16 +650,6 @@ async fn main() -> Result<()> {
                                         ReferenceOr::Reference { reference } => {
                                             let ref_data = parse_reference(reference);
 
-                                            if ref_data.1 == COMPONENT_PARAM {
-                                                referenced_component_params
-                                                    .push(ref_data.0.to_string());
-                                            } else if ref_data.1 == COMPONENT_SCHEMA {
-                                                referenced_component_schemas
-                                                    .push(ref_data.0.to_string());
-                                            } else {
-                                                // unhandled component type
-                                            }
-
                                             println!(
                                                 "Response reference name {} of type {}",
                                                 ref_data.0, ref_data.1
@@ -428,27 +682,132 @@ async fn main() -> Result<()> {
 
     println!();
 
-    println!("Report");
+    report_unused(openapi, report);
+}
 
-    for (param_name, _param_schema) in complex_component_params
-        .iter()
-        .chain(simple_component_params.iter())
-    {
-        if !referenced_component_params.contains(param_name) {
-            println!("Param {} is never used", param_name);
+/// Report components of every kind that nothing refers to, references from within a component
+/// to itself don't count
+pub fn report_unused(openapi: &OpenAPI, report: &mut Report) {
+    let Some(components) = openapi.components.as_ref() else {
+        return;
+    };
+
+    let references = walk::references(openapi);
+
+    let kinds: [(&str, &report::Rule, &str, Vec<&String>); 8] = [
+        (
+            COMPONENT_SCHEMA,
+            &report::UNUSED_SCHEMA,
+            "Schema",
+            components.schemas.keys().collect(),
+        ),
+        (
+            COMPONENT_PARAM,
+            &report::UNUSED_PARAM,
+            "Param",
+            components.parameters.keys().collect(),
+        ),
+        (
+            COMPONENT_RESPONSE,
+            &report::UNUSED_RESPONSE,
+            "Response",
+            components.responses.keys().collect(),
+        ),
+        (
+            COMPONENT_HEADER,
+            &report::UNUSED_HEADER,
+            "Header",
+            components.headers.keys().collect(),
+        ),
+        (
+            COMPONENT_REQUEST_BODY,
+            &report::UNUSED_REQUEST_BODY,
+            "Request body",
+            components.request_bodies.keys().collect(),
+        ),
+        (
+            COMPONENT_EXAMPLE,
+            &report::UNUSED_EXAMPLE,
+            "Example",
+            components.examples.keys().collect(),
+        ),
+        (
+            COMPONENT_LINK,
+            &report::UNUSED_LINK,
+            "Link",
+            components.links.keys().collect(),
+        ),
+        (
+            COMPONENT_CALLBACK,
+            &report::UNUSED_CALLBACK,
+            "Callback",
+            components.callbacks.keys().collect(),
+        ),
+    ];
+
+    for (kind, rule, label, names) in kinds {
+        for name in names {
+            if !references.contains(&walk::component_ref(kind, name)) {
+                report.add(
+                    rule,
+                    pointer(&["components", kind, name]),
+                    format!("{} {} is never used", label, name),
+                );
+            }
         }
     }
 
-    for (schema_name, _schema) in complex_component_schemas
-        .iter()
-        .chain(simple_component_schemas.iter())
-    {
-        if !referenced_component_schemas.contains(schema_name) {
-            println!("Schema {} is never used", schema_name);
+    for name in components.security_schemes.keys() {
+        if !references.contains(&walk::component_ref(COMPONENT_SECURITY_SCHEME, name)) {
+            report.add(
+                &report::UNUSED_SECURITY_SCHEME,
+                pointer(&["components", COMPONENT_SECURITY_SCHEME, name]),
+                format!("Security scheme {} is never required", name),
+            );
         }
     }
+}
 
-    Ok(())
+/// Report `in: path` parameters of one list, path or operation level, that the path template
+/// doesn't have or that aren't required
+fn check_declared_path_parameters(
+    components: &openapiv3::Components,
+    path: &str,
+    params: &[ReferenceOr<openapiv3::Parameter>],
+    tokens: &[&str],
+    report: &mut Report,
+) {
+    let variables = template_parts(path).1;
+
+    for (index, param) in params.iter().enumerate() {
+        let Some(openapiv3::Parameter::Path { parameter_data, .. }) =
+            walk::resolve_parameter(components, param)
+        else {
+            continue;
+        };
+
+        let index = index.to_string();
+        let location = pointer(&[tokens, &["parameters", &index]].concat());
+
+        if !variables.contains(&parameter_data.name.as_str()) {
+            report.add(
+                &report::UNKNOWN_PATH_PARAMETER,
+                location.clone(),
+                format!(
+                    "Path parameter {} is not a variable of {}",
+                    parameter_data.name, path
+                ),
+            );
+        }
+
+        if !parameter_data.required {
+            report.add(
+                &report::OPTIONAL_PATH_PARAMETER,
+                location,
+                format!("Path parameter {} must be required", parameter_data.name),
+            );
+        }
+    }
 }
 
 pub fn parse_reference(reference: &str) -> (&str, &str) {
@@ -481,20 +840,16 @@ pub fn is_complex(schema: &Schema) -> bool {
         },
         openapiv3::SchemaKind::OneOf { one_of } => one_of
             .iter()
-            .any(|schema| schema.as_item().map(|s| is_complex(s)).unwrap_or(false)),
+            .any(|schema| schema.as_item().map(is_complex).unwrap_or(false)),
         openapiv3::SchemaKind::AllOf { all_of } => all_of
             .iter()
-            .any(|schema| schema.as_item().map(|s| is_complex(s)).unwrap_or(false)),
+            .any(|schema| schema.as_item().map(is_complex).unwrap_or(false)),
         openapiv3::SchemaKind::AnyOf { any_of } => any_of
             .iter()
-            .any(|schema| schema.as_item().map(|s| is_complex(s)).unwrap_or(false)),
-        openapiv3::SchemaKind::Not { not } => not.as_item().map(|s| is_complex(s)).unwrap_or(false),
+            .any(|schema| schema.as_item().map(is_complex).unwrap_or(false)),
+        openapiv3::SchemaKind::Not { not } => not.as_item().map(is_complex).unwrap_or(false),
         openapiv3::SchemaKind::Any(schema_kind_any) => {
-            if schema_kind_any.items.is_some() || !schema_kind_any.enumeration.is_empty() {
-                true
-            } else {
-                false
-            }
+            schema_kind_any.items.is_some() || !schema_kind_any.enumeration.is_empty()
         }
     }
 }
@@ -0,0 +1,447 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use openapiv3::Schema;
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::fingerprint;
+use crate::lockfile::Lockfile;
+use crate::report::pointer;
+use crate::walk::Origin;
+use crate::COMPONENT_SCHEMA;
+
+pub const DEFAULT_HOISTED_NAME: &str = "{operationId}{location}{status}{paramName}";
+pub const DEFAULT_ENUM_NAME: &str = "{property}{paramName}";
+
+/// Variables a name template may use
+pub const VARIABLES: &[&str] = &[
+    "operationId",
+    "method",
+    "path",
+    "status",
+    "location",
+    "paramName",
+    "property",
+    "title",
+    "name",
+];
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    #[default]
+    Pascal,
+    Snake,
+    /// Keep the rendered template as is
+    Preserve,
+}
+
+impl FromStr for Case {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pascal" => Ok(Case::Pascal),
+            "snake" => Ok(Case::Snake),
+            "preserve" => Ok(Case::Preserve),
+            _ => Err(anyhow!(format!(
+                "Unknown case {}, expected pascal, snake or preserve",
+                s
+            ))),
+        }
+    }
+}
+
+enum Segment {
+    Literal(String),
+    Variable(String),
+}
+
+/// A parsed name template such as `{operationId}{location}{status}`
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!(format!("Unclosed variable in name template {}", s)))?;
+            let variable = &rest[start + 1..start + end];
+
+            if !VARIABLES.contains(&variable) {
+                return Err(anyhow!(format!(
+                    "Unknown variable {{{}}} in name template {}",
+                    variable, s
+                )));
+            }
+
+            segments.push(Segment::Variable(variable.to_string()));
+            rest = &rest[start + end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl Template {
+    pub fn uses(&self, variable: &str) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Variable(name) if name == variable))
+    }
+}
+
+/// Naming strategy for components created by passes
+pub struct Naming {
+    pub hoisted: Template,
+    /// Without a template merged schemas keep the first duplicate's name
+    pub merged: Option<Template>,
+    pub enums: Template,
+    pub case: Case,
+    /// Names from previous runs, reused before rendering templates
+    pub lock: Lockfile,
+}
+
+impl Naming {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            hoisted: config
+                .hoisted_name
+                .as_deref()
+                .unwrap_or(DEFAULT_HOISTED_NAME)
+                .parse()?,
+            merged: config.merged_name.as_deref().map(str::parse).transpose()?,
+            enums: config
+                .enum_name
+                .as_deref()
+                .unwrap_or(DEFAULT_ENUM_NAME)
+                .parse()?,
+            case: config.name_case.unwrap_or_default(),
+            lock: Lockfile::default(),
+        })
+    }
+
+    /// Name for a schema hoisted out of `origin`, locked names win over the template
+    pub fn hoisted_name(
+        &mut self,
+        origin: &Origin,
+        schema: &Schema,
+        taken: impl Fn(&str) -> bool,
+    ) -> Result<String> {
+        self.locked_name(origin, schema, taken, |naming| {
+            naming.name(&naming.hoisted, origin, schema)
+        })
+    }
+
+    /// Name for an enum extracted from its first inline copy at `origin`
+    pub fn enum_name(
+        &mut self,
+        origin: &Origin,
+        schema: &Schema,
+        taken: impl Fn(&str) -> bool,
+    ) -> Result<String> {
+        self.locked_name(origin, schema, taken, |naming| {
+            naming.name(&naming.enums, origin, schema)
+        })
+    }
+
+    fn locked_name(
+        &mut self,
+        origin: &Origin,
+        schema: &Schema,
+        taken: impl Fn(&str) -> bool,
+        render: impl Fn(&Self) -> String,
+    ) -> Result<String> {
+        let hash = fingerprint::hash(schema)?;
+
+        // names locked for other schemas stay theirs, even when those come later
+        let name = match self.lock.find(&hash, &origin.pointer) {
+            Some(name) if !taken(name) => name.to_string(),
+            _ => self.unique(render(self), &|candidate| {
+                taken(candidate) || self.lock.is_locked(candidate)
+            }),
+        };
+
+        self.lock.record(hash, origin.pointer.clone(), name.clone());
+
+        Ok(name)
+    }
+
+    /// Name for the survivor of identical component schemas, `first` being the first duplicate
+    pub fn merged_name(
+        &mut self,
+        first: &str,
+        schema: &Schema,
+        taken: impl Fn(&str) -> bool,
+    ) -> Result<String> {
+        let hash = fingerprint::hash(schema)?;
+        let origin = Origin {
+            pointer: pointer(&["components", COMPONENT_SCHEMA, first]),
+            name: Some(first.to_string()),
+            ..Default::default()
+        };
+
+        let name = match self.lock.find(&hash, &origin.pointer) {
+            Some(name) if !taken(name) => name.to_string(),
+            _ => {
+                let name = match &self.merged {
+                    Some(template) => self.name(template, &origin, schema),
+                    None => match schema.schema_data.title.as_deref() {
+                        Some(title) if !title.trim().is_empty() => {
+                            self.convert(&[title.to_string()])
+                        }
+                        _ => first.to_string(),
+                    },
+                };
+
+                self.unique(name, &|candidate| {
+                    taken(candidate) || (candidate != first && self.lock.is_locked(candidate))
+                })
+            }
+        };
+
+        self.lock.record(hash, origin.pointer, name.clone());
+
+        Ok(name)
+    }
+
+    /// Name for a schema. Its `title` wins over the template, unless the template uses `{title}` itself
+    pub fn name(&self, template: &Template, origin: &Origin, schema: &Schema) -> String {
+        let title = schema
+            .schema_data
+            .title
+            .as_deref()
+            .filter(|title| !title.trim().is_empty());
+
+        if let Some(title) = title {
+            if !template.uses("title") {
+                return self.convert(&[title.to_string()]);
+            }
+        }
+
+        let parts: Vec<String> = template
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Variable(variable) if variable == "title" => {
+                    title.unwrap_or_default().to_string()
+                }
+                Segment::Variable(variable) => origin.variable(variable).unwrap_or_default(),
+            })
+            .collect();
+
+        let name = self.convert(&parts);
+
+        if name.is_empty() {
+            return self.convert(&["Schema".to_string()]);
+        }
+
+        name
+    }
+
+    /// Join rendered template parts in the configured case
+    pub fn convert(&self, parts: &[String]) -> String {
+        if self.case == Case::Preserve {
+            return parts.concat();
+        }
+
+        let words: Vec<String> = parts.iter().flat_map(|part| split_words(part)).collect();
+
+        match self.case {
+            Case::Pascal => words
+                .iter()
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => {
+                            first.to_uppercase().collect::<String>()
+                                + &chars.as_str().to_lowercase()
+                        }
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            Case::Snake => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<String>>()
+                .join("_"),
+            Case::Preserve => parts.concat(),
+        }
+    }
+
+    /// First variation of `name` that `taken` doesn't reject
+    pub fn unique(&self, name: String, taken: &impl Fn(&str) -> bool) -> String {
+        if !taken(&name) {
+            return name;
+        }
+
+        let separator = if self.case == Case::Snake { "_" } else { "" };
+
+        (2..)
+            .map(|index| format!("{}{}{}", name, separator, index))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or(name)
+    }
+}
+
+/// Split on non-alphanumeric characters and on camelCase boundaries
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for chunk in text.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let chars: Vec<char> = chunk.chars().collect();
+        let mut word = String::new();
+
+        for (index, c) in chars.iter().enumerate() {
+            let boundary = index > 0
+                && c.is_ascii_uppercase()
+                && (chars[index - 1].is_ascii_lowercase()
+                    || chars[index - 1].is_ascii_digit()
+                    || chars
+                        .get(index + 1)
+                        .map(|next| next.is_ascii_lowercase())
+                        .unwrap_or(false));
+
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+
+            word.push(*c);
+        }
+
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naming(case: Case) -> Naming {
+        let mut naming = Naming::from_config(&Config::default()).unwrap();
+        naming.case = case;
+        naming
+    }
+
+    fn response(path: &str) -> Origin {
+        Origin {
+            pointer: pointer(&["paths", path, "get", "responses", "200"]),
+            path: Some(path.to_string()),
+            status: Some("200".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn schema(value: serde_json::Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn locked_names_survive_new_schemas_taking_their_template() {
+        let lockfile = std::env::temp_dir().join(format!("names-{}.lock", std::process::id()));
+        let b =
+            schema(serde_json::json!({"type": "object", "properties": {"b": {"type": "string"}}}));
+        let a =
+            schema(serde_json::json!({"type": "object", "properties": {"a": {"type": "string"}}}));
+
+        let mut first = naming(Case::Pascal);
+        first.hoisted = "Inline{status}".parse().unwrap();
+        let name = first.hoisted_name(&response("/b"), &b, |_| false).unwrap();
+        assert_eq!(name, "Inline200");
+        first.lock.save(&lockfile).unwrap();
+
+        // upstream adds /a before /b
+        let mut second = naming(Case::Pascal);
+        second.hoisted = "Inline{status}".parse().unwrap();
+        second.lock = Lockfile::load(&lockfile).unwrap();
+        std::fs::remove_file(&lockfile).unwrap();
+
+        let new = second.hoisted_name(&response("/a"), &a, |_| false).unwrap();
+        let existing = second
+            .hoisted_name(&response("/b"), &b, |candidate| candidate == new)
+            .unwrap();
+
+        assert_eq!(existing, "Inline200");
+        assert_eq!(new, "Inline2002");
+    }
+
+    #[test]
+    fn template_parses_variables_and_literals() {
+        let template: Template = "{operationId}Body{status}".parse().unwrap();
+
+        assert!(template.uses("operationId"));
+        assert!(template.uses("status"));
+        assert!(!template.uses("location"));
+        assert!(matches!(&template.segments[1], Segment::Literal(text) if text == "Body"));
+    }
+
+    #[test]
+    fn template_rejects_unknown_and_unclosed_variables() {
+        assert!("{operation}".parse::<Template>().is_err());
+        assert!("{operationId".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn split_words_on_separators_and_case() {
+        assert_eq!(split_words("getUserById"), ["get", "User", "By", "Id"]);
+        assert_eq!(split_words("HTTPResponse_v2"), ["HTTP", "Response", "v2"]);
+        assert_eq!(split_words("/users/{id}"), ["users", "id"]);
+        assert!(split_words("--").is_empty());
+    }
+
+    #[test]
+    fn convert_joins_parts_in_case() {
+        let parts = [
+            "listUsers".to_string(),
+            "response".to_string(),
+            "200".to_string(),
+        ];
+
+        assert_eq!(naming(Case::Pascal).convert(&parts), "ListUsersResponse200");
+        assert_eq!(
+            naming(Case::Snake).convert(&parts),
+            "list_users_response_200"
+        );
+        assert_eq!(
+            naming(Case::Preserve).convert(&parts),
+            "listUsersresponse200"
+        );
+    }
+
+    #[test]
+    fn unique_appends_a_number() {
+        let taken = |name: &str| name == "User" || name == "User2";
+        assert_eq!(
+            naming(Case::Pascal).unique("User".to_string(), &taken),
+            "User3"
+        );
+
+        let taken = |name: &str| name == "user";
+        assert_eq!(
+            naming(Case::Snake).unique("user".to_string(), &taken),
+            "user_2"
+        );
+    }
+}
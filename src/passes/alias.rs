@@ -0,0 +1,248 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::parse_reference;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_PARAM;
+use crate::COMPONENT_RESPONSE;
+use crate::COMPONENT_SCHEMA;
+
+/// Extension on `components` listing kept aliases, alias reference => target reference
+pub const ALIAS_EXTENSION: &str = "x-deprecated-alias";
+
+/// What happens to alias components once nothing refers to them
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Aliases {
+    #[default]
+    Remove,
+    /// Keep them pointing straight at their target and list them in `x-deprecated-alias`
+    Keep,
+}
+
+impl FromStr for Aliases {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "remove" => Ok(Aliases::Remove),
+            "keep" => Ok(Aliases::Keep),
+            _ => Err(anyhow!(format!(
+                "Unknown alias handling {}, expected remove or keep",
+                s
+            ))),
+        }
+    }
+}
+
+/// Point references to alias components, schemas, parameters and responses that are only a
+/// `$ref`, straight at the end of the alias chain
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let Some(components) = openapi.components.as_ref() else {
+        return Ok(());
+    };
+
+    // alias reference => final target
+    let mut targets = chains(COMPONENT_SCHEMA, &components.schemas);
+    targets.extend(chains(COMPONENT_PARAM, &components.parameters));
+    targets.extend(chains(COMPONENT_RESPONSE, &components.responses));
+
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    for (alias, target) in targets.iter() {
+        println!("Collapsing alias {} => {}", alias, target);
+    }
+
+    walk::rewrite_references(openapi, &|reference| targets.get(reference).cloned())?;
+
+    let Some(components) = openapi.components.as_mut() else {
+        return Ok(());
+    };
+
+    match config.aliases.unwrap_or_default() {
+        Aliases::Remove => {
+            components
+                .schemas
+                .retain(|name, _| !targets.contains_key(&component_ref(COMPONENT_SCHEMA, name)));
+            components
+                .parameters
+                .retain(|name, _| !targets.contains_key(&component_ref(COMPONENT_PARAM, name)));
+            components
+                .responses
+                .retain(|name, _| !targets.contains_key(&component_ref(COMPONENT_RESPONSE, name)));
+        }
+        Aliases::Keep => {
+            let listed = components
+                .extensions
+                .entry(ALIAS_EXTENSION.to_string())
+                .or_insert_with(|| Value::Object(Default::default()));
+
+            if let Value::Object(listed) = listed {
+                for (alias, target) in targets {
+                    listed.insert(alias, Value::String(target));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Alias reference => the first non-alias at the end of its chain, skipping chains that loop
+fn chains<T>(
+    kind: &str,
+    components: &IndexMap<String, ReferenceOr<T>>,
+) -> IndexMap<String, String> {
+    let mut targets = IndexMap::new();
+
+    'aliases: for (name, component) in components.iter() {
+        let ReferenceOr::Reference { reference } = component else {
+            continue;
+        };
+
+        let alias = component_ref(kind, name);
+        let mut seen = HashSet::from([alias.clone()]);
+        let mut target = reference.clone();
+
+        loop {
+            let (next_name, next_kind) = parse_reference(&target);
+            if next_kind != kind || !target.starts_with("#/components/") {
+                break;
+            }
+
+            match components.get(next_name) {
+                Some(ReferenceOr::Reference { reference: next }) => {
+                    if !seen.insert(target.clone()) || seen.contains(next) {
+                        println!("Alias {} is part of a loop, leaving it", alias);
+                        continue 'aliases;
+                    }
+
+                    target = next.clone();
+                }
+                _ => break,
+            }
+        }
+
+        targets.insert(alias, target);
+    }
+
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn collapse(aliases: Aliases) -> Value {
+        let config = Config {
+            aliases: Some(aliases),
+            ..Default::default()
+        };
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {"/users": {"get": {
+                "parameters": [{"$ref": "#/components/parameters/PageAlias"}],
+                "responses": {"200": {"$ref": "#/components/responses/UsersAlias"}}
+            }}},
+            "components": {
+                "schemas": {
+                    "User": {"type": "object", "properties": {"pet": {"$ref": "#/components/schemas/A"}}},
+                    "A": {"$ref": "#/components/schemas/B"},
+                    "B": {"$ref": "#/components/schemas/Pet"},
+                    "Pet": {"type": "object"},
+                    "Ping": {"$ref": "#/components/schemas/Pong"},
+                    "Pong": {"$ref": "#/components/schemas/Ping"}
+                },
+                "parameters": {
+                    "PageAlias": {"$ref": "#/components/parameters/Page"},
+                    "Page": {"name": "page", "in": "query", "schema": {"type": "integer"}}
+                },
+                "responses": {
+                    "UsersAlias": {"$ref": "#/components/responses/Users"},
+                    "Users": {"description": "users"}
+                }
+            }
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()
+    }
+
+    fn names(map: &Value) -> Vec<&String> {
+        map.as_object().unwrap().keys().collect()
+    }
+
+    #[test]
+    fn points_references_at_the_end_of_the_chain_and_removes_aliases() {
+        let document = collapse(Aliases::Remove);
+        let components = &document["components"];
+
+        assert_eq!(
+            names(&components["schemas"]),
+            ["User", "Pet", "Ping", "Pong"]
+        );
+        assert_eq!(names(&components["parameters"]), ["Page"]);
+        assert_eq!(names(&components["responses"]), ["Users"]);
+        assert_eq!(
+            components["schemas"]["User"]["properties"]["pet"],
+            json!({"$ref": "#/components/schemas/Pet"})
+        );
+
+        let operation = &document["paths"]["/users"]["get"];
+        assert_eq!(
+            operation["parameters"][0],
+            json!({"$ref": "#/components/parameters/Page"})
+        );
+        assert_eq!(
+            operation["responses"]["200"],
+            json!({"$ref": "#/components/responses/Users"})
+        );
+    }
+
+    #[test]
+    fn keeps_aliases_pointing_at_their_target() {
+        let document = collapse(Aliases::Keep);
+        let components = &document["components"];
+
+        assert_eq!(
+            components["schemas"]["A"],
+            json!({"$ref": "#/components/schemas/Pet"})
+        );
+        assert_eq!(
+            components["schemas"]["B"],
+            json!({"$ref": "#/components/schemas/Pet"})
+        );
+        assert_eq!(
+            components[ALIAS_EXTENSION],
+            json!({
+                "#/components/schemas/A": "#/components/schemas/Pet",
+                "#/components/schemas/B": "#/components/schemas/Pet",
+                "#/components/parameters/PageAlias": "#/components/parameters/Page",
+                "#/components/responses/UsersAlias": "#/components/responses/Users"
+            })
+        );
+    }
+}
@@ -0,0 +1,663 @@
+use anyhow::Result;
+use openapiv3::AdditionalProperties;
+use openapiv3::AnySchema;
+use openapiv3::ArrayType;
+use openapiv3::BooleanType;
+use openapiv3::Components;
+use openapiv3::IntegerType;
+use openapiv3::NumberType;
+use openapiv3::ObjectType;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaData;
+use openapiv3::SchemaKind;
+use openapiv3::StringType;
+use openapiv3::Type;
+use openapiv3::VariantOrUnknownOrEmpty;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::report::Rule;
+use crate::report::Severity;
+use crate::walk;
+
+pub const ALL_OF_CONFLICT: Rule = Rule {
+    name: "all-of-conflict",
+    severity: Severity::Warning,
+};
+
+/// Replace `allOf` compositions of object schemas, referenced ones included, with a single object schema.
+///
+/// Properties and `required` are unioned and constraints intersected. Compositions whose members
+/// define the same property incompatibly are reported and left in place, and so are compositions
+/// extending a schema with a `discriminator`, which would lose their place in its hierarchy.
+/// Annotations of inline members move to the flattened schema where it has none of its own.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    report: &mut Report,
+) -> Result<()> {
+    // references resolve against the document as it was before flattening
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        let ReferenceOr::Item(schema) = slot else {
+            return Ok(());
+        };
+
+        let members = match &schema.schema_kind {
+            SchemaKind::AllOf { all_of } => all_of.clone(),
+            SchemaKind::Any(any) => any.all_of.clone(),
+            _ => Vec::new(),
+        };
+
+        if members.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(parent) = discriminated_parent(&members, &components, &mut Vec::new()) {
+            println!(
+                "Leaving allOf at {} in place, {} has a discriminator",
+                origin.pointer, parent
+            );
+            return Ok(());
+        }
+
+        match object_of(schema, &components, &mut Vec::new()) {
+            Ok(object) => {
+                println!("Flattening allOf at {}", origin.pointer);
+                schema.schema_kind = SchemaKind::Type(Type::Object(object));
+
+                let dropped = absorb_member_data(&mut schema.schema_data, &members);
+                if !dropped.is_empty() {
+                    println!(
+                        "Dropping {} of allOf members at {}, the flattened schema says otherwise",
+                        dropped.join(", "),
+                        origin.pointer
+                    );
+                }
+            }
+            Err(Unflattenable::Conflicts(conflicts)) => {
+                for conflict in conflicts {
+                    report.add(&ALL_OF_CONFLICT, origin.pointer.clone(), conflict);
+                }
+            }
+            Err(Unflattenable::NotObject(reason)) => {
+                println!("Leaving allOf at {} in place, {}", origin.pointer, reason);
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// The first schema with a `discriminator` the members refer to, directly or through their own
+/// `allOf`
+fn discriminated_parent(
+    members: &[ReferenceOr<Schema>],
+    components: &Components,
+    visiting: &mut Vec<String>,
+) -> Option<String> {
+    for member in members.iter() {
+        let schema = match member {
+            ReferenceOr::Reference { reference } => {
+                if visiting.contains(reference) {
+                    continue;
+                }
+
+                let schema = walk::resolve_schema(components, reference)?;
+                if schema.schema_data.discriminator.is_some() {
+                    return Some(reference.clone());
+                }
+
+                visiting.push(reference.clone());
+                schema
+            }
+            ReferenceOr::Item(schema) => schema,
+        };
+
+        let nested = match &schema.schema_kind {
+            SchemaKind::AllOf { all_of } => all_of,
+            SchemaKind::Any(any) => &any.all_of,
+            _ => continue,
+        };
+
+        if let Some(parent) = discriminated_parent(nested, components, visiting) {
+            return Some(parent);
+        }
+    }
+
+    None
+}
+
+/// Move annotations of inline members to the flattened schema where it has none, returning the
+/// names of those it already has differently
+fn absorb_member_data(data: &mut SchemaData, members: &[ReferenceOr<Schema>]) -> Vec<String> {
+    fn absorb<T: PartialEq>(
+        name: &str,
+        target: &mut Option<T>,
+        value: Option<T>,
+        dropped: &mut Vec<String>,
+    ) {
+        match (target.as_ref(), value) {
+            (_, None) => {}
+            (None, value) => *target = value,
+            (Some(existing), Some(value)) if *existing == value => {}
+            (Some(_), Some(_)) => dropped.push(name.to_string()),
+        }
+    }
+
+    let mut dropped = Vec::new();
+
+    for member in members.iter() {
+        let ReferenceOr::Item(member) = member else {
+            continue;
+        };
+
+        let member = member.schema_data.clone();
+        absorb("title", &mut data.title, member.title, &mut dropped);
+        absorb(
+            "description",
+            &mut data.description,
+            member.description,
+            &mut dropped,
+        );
+        absorb("default", &mut data.default, member.default, &mut dropped);
+        absorb("example", &mut data.example, member.example, &mut dropped);
+        absorb(
+            "externalDocs",
+            &mut data.external_docs,
+            member.external_docs,
+            &mut dropped,
+        );
+        data.read_only |= member.read_only;
+        data.write_only |= member.write_only;
+        data.deprecated |= member.deprecated;
+
+        // one nullable member doesn't make the intersection nullable
+        if member.nullable && !data.nullable {
+            dropped.push("nullable".to_string());
+        }
+        if member.discriminator.is_some() {
+            dropped.push("discriminator".to_string());
+        }
+        for (key, value) in member.extensions {
+            data.extensions.entry(key).or_insert(value);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    dropped.retain(|name| seen.insert(name.clone()));
+    dropped
+}
+
+/// The object schema `schema` amounts to once its `allOf` members are merged, if it is one
+pub fn flattened(schema: &Schema, components: &Components) -> Option<ObjectType> {
+    object_of(schema, components, &mut Vec::new()).ok()
+}
+
+enum Unflattenable {
+    /// Members define the same property incompatibly
+    Conflicts(Vec<String>),
+    /// A member isn't an object schema
+    NotObject(String),
+}
+
+/// The object schema a schema amounts to, with any `allOf` merged in
+fn object_of(
+    schema: &Schema,
+    components: &Components,
+    visiting: &mut Vec<String>,
+) -> Result<ObjectType, Unflattenable> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(object)) => Ok(object.clone()),
+        SchemaKind::AllOf { all_of } => merge_members(all_of, components, visiting),
+        SchemaKind::Any(any) if is_object_like(any) => {
+            let own = ObjectType {
+                properties: any.properties.clone(),
+                required: any.required.clone(),
+                additional_properties: any.additional_properties.clone(),
+                min_properties: any.min_properties,
+                max_properties: any.max_properties,
+            };
+
+            if any.all_of.is_empty() {
+                return Ok(own);
+            }
+
+            let mut merged = merge_members(&any.all_of, components, visiting)?;
+            let mut conflicts = Vec::new();
+            merge_objects(&mut merged, &own, &mut conflicts);
+
+            if conflicts.is_empty() {
+                Ok(merged)
+            } else {
+                Err(Unflattenable::Conflicts(conflicts))
+            }
+        }
+        _ => Err(Unflattenable::NotObject(format!(
+            "a member is {}",
+            describe(schema)
+        ))),
+    }
+}
+
+fn merge_members(
+    members: &[ReferenceOr<Schema>],
+    components: &Components,
+    visiting: &mut Vec<String>,
+) -> Result<ObjectType, Unflattenable> {
+    let mut merged = ObjectType::default();
+    let mut conflicts = Vec::new();
+
+    for member in members.iter() {
+        let object = match member {
+            ReferenceOr::Reference { reference } => {
+                if visiting.contains(reference) {
+                    return Err(Unflattenable::NotObject(format!(
+                        "{} refers to itself",
+                        reference
+                    )));
+                }
+
+                let schema = walk::resolve_schema(components, reference).ok_or_else(|| {
+                    Unflattenable::NotObject(format!("{} can't be resolved", reference))
+                })?;
+
+                visiting.push(reference.clone());
+                let object = object_of(schema, components, visiting);
+                visiting.pop();
+
+                object?
+            }
+            ReferenceOr::Item(schema) => object_of(schema, components, visiting)?,
+        };
+
+        merge_objects(&mut merged, &object, &mut conflicts);
+    }
+
+    if conflicts.is_empty() {
+        Ok(merged)
+    } else {
+        Err(Unflattenable::Conflicts(conflicts))
+    }
+}
+
+fn is_object_like(any: &AnySchema) -> bool {
+    matches!(any.typ.as_deref(), None | Some("object"))
+        && any.pattern.is_none()
+        && any.multiple_of.is_none()
+        && any.minimum.is_none()
+        && any.maximum.is_none()
+        && any.items.is_none()
+        && any.enumeration.is_empty()
+        && any.format.is_none()
+        && any.min_length.is_none()
+        && any.max_length.is_none()
+        && any.one_of.is_empty()
+        && any.any_of.is_empty()
+        && any.not.is_none()
+}
+
+/// Merge `object` into `target`, collecting properties that can't be intersected
+fn merge_objects(target: &mut ObjectType, object: &ObjectType, conflicts: &mut Vec<String>) {
+    for (name, property) in object.properties.iter() {
+        match target.properties.get(name) {
+            None => {
+                target.properties.insert(name.clone(), property.clone());
+            }
+            Some(existing) => match intersect_property(existing, property) {
+                Some(intersection) => {
+                    target.properties.insert(name.clone(), intersection);
+                }
+                None => conflicts.push(format!(
+                    "Property {} is {} in one allOf member and {} in another",
+                    name,
+                    describe_slot(existing),
+                    describe_slot(property)
+                )),
+            },
+        }
+    }
+
+    for required in object.required.iter() {
+        if !target.required.contains(required) {
+            target.required.push(required.clone());
+        }
+    }
+
+    target.additional_properties = match (
+        target.additional_properties.take(),
+        object.additional_properties.clone(),
+    ) {
+        (None, other) | (other, None) => other,
+        (Some(AdditionalProperties::Any(false)), _)
+        | (_, Some(AdditionalProperties::Any(false))) => Some(AdditionalProperties::Any(false)),
+        (Some(AdditionalProperties::Any(true)), other)
+        | (other, Some(AdditionalProperties::Any(true))) => other,
+        (Some(a), Some(b)) => {
+            if a != b {
+                conflicts.push("additionalProperties differ between allOf members".to_string());
+            }
+            Some(a)
+        }
+    };
+
+    target.min_properties = tighter(target.min_properties, object.min_properties, true);
+    target.max_properties = tighter(target.max_properties, object.max_properties, false);
+}
+
+fn intersect_property(
+    a: &ReferenceOr<Box<Schema>>,
+    b: &ReferenceOr<Box<Schema>>,
+) -> Option<ReferenceOr<Box<Schema>>> {
+    if a == b {
+        return Some(a.clone());
+    }
+
+    match (a, b) {
+        (ReferenceOr::Item(a), ReferenceOr::Item(b)) => {
+            intersect_schema(a, b).map(ReferenceOr::boxed_item)
+        }
+        _ => None,
+    }
+}
+
+/// A schema accepting what both `a` and `b` accept, if one can be expressed without `allOf`
+fn intersect_schema(a: &Schema, b: &Schema) -> Option<Schema> {
+    let schema_kind = match (&a.schema_kind, &b.schema_kind) {
+        (SchemaKind::Type(Type::String(x)), SchemaKind::Type(Type::String(y))) => {
+            SchemaKind::Type(Type::String(StringType {
+                format: same_format(&x.format, &y.format)?,
+                pattern: same(&x.pattern, &y.pattern)?,
+                enumeration: intersect_enum(&x.enumeration, &y.enumeration)?,
+                min_length: tighter(x.min_length, y.min_length, true),
+                max_length: tighter(x.max_length, y.max_length, false),
+            }))
+        }
+        (SchemaKind::Type(Type::Integer(x)), SchemaKind::Type(Type::Integer(y))) => {
+            let (minimum, exclusive_minimum) = tighter_bound(
+                (x.minimum, x.exclusive_minimum),
+                (y.minimum, y.exclusive_minimum),
+                true,
+            );
+            let (maximum, exclusive_maximum) = tighter_bound(
+                (x.maximum, x.exclusive_maximum),
+                (y.maximum, y.exclusive_maximum),
+                false,
+            );
+
+            SchemaKind::Type(Type::Integer(IntegerType {
+                format: same_format(&x.format, &y.format)?,
+                multiple_of: same(&x.multiple_of, &y.multiple_of)?,
+                exclusive_minimum,
+                exclusive_maximum,
+                minimum,
+                maximum,
+                enumeration: intersect_enum(&x.enumeration, &y.enumeration)?,
+            }))
+        }
+        (SchemaKind::Type(Type::Number(x)), SchemaKind::Type(Type::Number(y))) => {
+            let (minimum, exclusive_minimum) = tighter_bound(
+                (x.minimum, x.exclusive_minimum),
+                (y.minimum, y.exclusive_minimum),
+                true,
+            );
+            let (maximum, exclusive_maximum) = tighter_bound(
+                (x.maximum, x.exclusive_maximum),
+                (y.maximum, y.exclusive_maximum),
+                false,
+            );
+
+            SchemaKind::Type(Type::Number(NumberType {
+                format: same_format(&x.format, &y.format)?,
+                multiple_of: same(&x.multiple_of, &y.multiple_of)?,
+                exclusive_minimum,
+                exclusive_maximum,
+                minimum,
+                maximum,
+                enumeration: intersect_enum(&x.enumeration, &y.enumeration)?,
+            }))
+        }
+        (SchemaKind::Type(Type::Boolean(x)), SchemaKind::Type(Type::Boolean(y))) => {
+            SchemaKind::Type(Type::Boolean(BooleanType {
+                enumeration: intersect_enum(&x.enumeration, &y.enumeration)?,
+            }))
+        }
+        (SchemaKind::Type(Type::Array(x)), SchemaKind::Type(Type::Array(y))) => {
+            SchemaKind::Type(Type::Array(ArrayType {
+                items: same(&x.items, &y.items)?,
+                min_items: tighter(x.min_items, y.min_items, true),
+                max_items: tighter(x.max_items, y.max_items, false),
+                unique_items: x.unique_items || y.unique_items,
+            }))
+        }
+        (SchemaKind::Type(Type::Object(x)), SchemaKind::Type(Type::Object(y))) => {
+            let mut merged = x.clone();
+            let mut conflicts = Vec::new();
+            merge_objects(&mut merged, y, &mut conflicts);
+
+            if !conflicts.is_empty() {
+                return None;
+            }
+
+            SchemaKind::Type(Type::Object(merged))
+        }
+        (x, y) if x == y => x.clone(),
+        _ => return None,
+    };
+
+    let mut schema_data = a.schema_data.clone();
+    schema_data.nullable = a.schema_data.nullable && b.schema_data.nullable;
+    schema_data.read_only |= b.schema_data.read_only;
+    schema_data.write_only |= b.schema_data.write_only;
+    schema_data.deprecated |= b.schema_data.deprecated;
+    if schema_data.title.is_none() {
+        schema_data.title = b.schema_data.title.clone();
+    }
+    if schema_data.description.is_none() {
+        schema_data.description = b.schema_data.description.clone();
+    }
+
+    Some(Schema {
+        schema_data,
+        schema_kind,
+    })
+}
+
+/// Either value when only one is set, `None` when both are set and differ
+fn same<T: Clone + PartialEq>(a: &Option<T>, b: &Option<T>) -> Option<Option<T>> {
+    match (a, b) {
+        (None, other) | (other, None) => Some(other.clone()),
+        (Some(x), Some(y)) if x == y => Some(a.clone()),
+        _ => None,
+    }
+}
+
+fn same_format<T: Clone + PartialEq>(
+    a: &VariantOrUnknownOrEmpty<T>,
+    b: &VariantOrUnknownOrEmpty<T>,
+) -> Option<VariantOrUnknownOrEmpty<T>> {
+    if a.is_empty() {
+        Some(b.clone())
+    } else if b.is_empty() || a == b {
+        Some(a.clone())
+    } else {
+        None
+    }
+}
+
+/// Values allowed by both enumerations, `None` when nothing would be left
+fn intersect_enum<T: Clone + PartialEq>(a: &[T], b: &[T]) -> Option<Vec<T>> {
+    if a.is_empty() {
+        return Some(b.to_vec());
+    }
+
+    if b.is_empty() {
+        return Some(a.to_vec());
+    }
+
+    let values: Vec<T> = a
+        .iter()
+        .filter(|value| b.contains(value))
+        .cloned()
+        .collect();
+
+    (!values.is_empty()).then_some(values)
+}
+
+/// The larger of two lower bounds, or the smaller of two upper bounds
+fn tighter<T: PartialOrd + Copy>(a: Option<T>, b: Option<T>, lower: bool) -> Option<T> {
+    match (a, b) {
+        (None, other) | (other, None) => other,
+        (Some(x), Some(y)) => Some(if (x > y) == lower { x } else { y }),
+    }
+}
+
+/// Like `tighter`, for bounds that can be exclusive
+fn tighter_bound<T: PartialOrd + Copy>(
+    a: (Option<T>, bool),
+    b: (Option<T>, bool),
+    lower: bool,
+) -> (Option<T>, bool) {
+    match (a.0, b.0) {
+        (None, _) => b,
+        (_, None) => a,
+        (Some(x), Some(y)) if x == y => (Some(x), a.1 || b.1),
+        (Some(x), Some(y)) => {
+            if (x > y) == lower {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+fn describe(schema: &Schema) -> String {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(_)) => "a string".to_string(),
+        SchemaKind::Type(Type::Number(_)) => "a number".to_string(),
+        SchemaKind::Type(Type::Integer(_)) => "an integer".to_string(),
+        SchemaKind::Type(Type::Object(_)) => "an object".to_string(),
+        SchemaKind::Type(Type::Array(_)) => "an array".to_string(),
+        SchemaKind::Type(Type::Boolean(_)) => "a boolean".to_string(),
+        SchemaKind::OneOf { .. } => "a oneOf".to_string(),
+        SchemaKind::AllOf { .. } => "an allOf".to_string(),
+        SchemaKind::AnyOf { .. } => "an anyOf".to_string(),
+        SchemaKind::Not { .. } => "a not".to_string(),
+        SchemaKind::Any(any) => match any.typ.as_deref() {
+            Some(typ) => format!("an untyped {} schema", typ),
+            None => "an untyped schema".to_string(),
+        },
+    }
+}
+
+fn describe_slot(slot: &ReferenceOr<Box<Schema>>) -> String {
+    match slot {
+        ReferenceOr::Reference { reference } => format!("a reference to {}", reference),
+        ReferenceOr::Item(schema) => describe(schema),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(value: serde_json::Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn tighter_bound_keeps_the_stricter_limit() {
+        assert_eq!(
+            tighter_bound((Some(1), false), (Some(3), false), true),
+            (Some(3), false)
+        );
+        assert_eq!(
+            tighter_bound((Some(1), false), (Some(3), false), false),
+            (Some(1), false)
+        );
+        assert_eq!(
+            tighter_bound((None, false), (Some(3), true), true),
+            (Some(3), true)
+        );
+        assert_eq!(
+            tighter_bound((Some(2), false), (None, false), false),
+            (Some(2), false)
+        );
+    }
+
+    #[test]
+    fn tighter_bound_is_exclusive_when_either_equal_bound_is() {
+        assert_eq!(
+            tighter_bound((Some(2), true), (Some(2), false), true),
+            (Some(2), true)
+        );
+        assert_eq!(
+            tighter_bound((Some(2), false), (Some(2), true), false),
+            (Some(2), true)
+        );
+    }
+
+    #[test]
+    fn intersect_schema_narrows_constraints() {
+        let a = schema(json!({"type": "integer", "minimum": 0, "maximum": 10, "description": "a"}));
+        let b = schema(
+            json!({"type": "integer", "minimum": 5, "exclusiveMaximum": true, "maximum": 10}),
+        );
+
+        assert_eq!(
+            intersect_schema(&a, &b),
+            Some(schema(json!({
+                "type": "integer",
+                "minimum": 5,
+                "maximum": 10,
+                "exclusiveMaximum": true,
+                "description": "a"
+            })))
+        );
+    }
+
+    #[test]
+    fn intersect_schema_intersects_enums_and_nullability() {
+        let a = schema(json!({"type": "string", "enum": ["a", "b"], "nullable": true}));
+        let b = schema(json!({"type": "string", "enum": ["b", "c"], "maxLength": 3}));
+
+        assert_eq!(
+            intersect_schema(&a, &b),
+            Some(schema(
+                json!({"type": "string", "enum": ["b"], "maxLength": 3})
+            ))
+        );
+    }
+
+    #[test]
+    fn intersect_schema_rejects_incompatible_schemas() {
+        let string = schema(json!({"type": "string"}));
+
+        assert_eq!(
+            intersect_schema(&string, &schema(json!({"type": "integer"}))),
+            None
+        );
+        assert_eq!(
+            intersect_schema(
+                &schema(json!({"type": "string", "enum": ["a"]})),
+                &schema(json!({"type": "string", "enum": ["b"]}))
+            ),
+            None
+        );
+        assert_eq!(
+            intersect_schema(
+                &schema(json!({"type": "string", "format": "date"})),
+                &schema(json!({"type": "string", "format": "uuid"}))
+            ),
+            None
+        );
+    }
+}
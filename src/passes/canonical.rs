@@ -0,0 +1,363 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use serde::Deserialize;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::parse_reference;
+use crate::report::Report;
+use crate::COMPONENT_SCHEMA;
+
+/// Extension marking schemas that are part of a reference cycle, listing the cycle
+pub const CYCLE_EXTENSION: &str = "x-normalizer-cycle";
+
+/// Order schema keywords are written in, keywords not listed follow in their original order
+pub const KEYWORD_ORDER: &[&str] = &[
+    "$ref",
+    "type",
+    "format",
+    "title",
+    "description",
+    "properties",
+    "required",
+    "additionalProperties",
+    "minProperties",
+    "maxProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "enum",
+    "default",
+    "nullable",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "discriminator",
+    "oneOf",
+    "anyOf",
+    "allOf",
+    "not",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "example",
+    "externalDocs",
+];
+
+/// How `components.schemas` is ordered
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentOrder {
+    #[default]
+    Alphabetical,
+    /// Schemas after the schemas they reference, cycles grouped and marked
+    Dependency,
+}
+
+impl FromStr for ComponentOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alphabetical" => Ok(ComponentOrder::Alphabetical),
+            "dependency" => Ok(ComponentOrder::Dependency),
+            _ => Err(anyhow!(format!(
+                "Unknown component order {}, expected alphabetical or dependency",
+                s
+            ))),
+        }
+    }
+}
+
+/// Sort every `components` map so reruns and reordered inputs give the same document.
+///
+/// Paths and operations keep the order they have in the input.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let Some(components) = openapi.components.as_mut() else {
+        return Ok(());
+    };
+
+    // marks from an earlier run may be stale
+    for schema in components.schemas.values_mut() {
+        if let ReferenceOr::Item(schema) = schema {
+            schema.schema_data.extensions.shift_remove(CYCLE_EXTENSION);
+        }
+    }
+
+    components.schemas.sort_keys();
+    if config.component_order.unwrap_or_default() == ComponentOrder::Dependency {
+        components.schemas = dependency_order(std::mem::take(&mut components.schemas));
+    }
+
+    components.responses.sort_keys();
+    components.parameters.sort_keys();
+    components.examples.sort_keys();
+    components.request_bodies.sort_keys();
+    components.headers.sort_keys();
+    components.security_schemes.sort_keys();
+    components.links.sort_keys();
+    components.callbacks.sort_keys();
+
+    Ok(())
+}
+
+/// Put schema keywords in `KEYWORD_ORDER`, wherever a schema appears in the serialized document
+pub fn render(document: &mut Value) {
+    match document {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match key.as_str() {
+                    "schema" => order_schema(child),
+                    "schemas" => {
+                        if let Value::Object(schemas) = child {
+                            schemas.values_mut().for_each(order_schema);
+                        }
+                    }
+                    // free form values, which may look like anything. A schema's `default` is
+                    // left alone by `order_schema`, here `default` is the catch-all response
+                    "example" | "examples" | "enum" => {}
+                    key if key.starts_with("x-") => {}
+                    _ => render(child),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(render),
+        _ => {}
+    }
+}
+
+fn order_schema(schema: &mut Value) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+
+    let mut ordered = Map::new();
+    for keyword in KEYWORD_ORDER.iter() {
+        if let Some(value) = map.shift_remove(*keyword) {
+            ordered.insert(keyword.to_string(), value);
+        }
+    }
+    ordered.extend(std::mem::take(map));
+
+    for (keyword, child) in ordered.iter_mut() {
+        match keyword.as_str() {
+            "properties" => {
+                if let Value::Object(properties) = child {
+                    properties.values_mut().for_each(order_schema);
+                }
+            }
+            "items" | "additionalProperties" | "not" => order_schema(child),
+            "oneOf" | "anyOf" | "allOf" => {
+                if let Value::Array(members) = child {
+                    members.iter_mut().for_each(order_schema);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    *map = ordered;
+}
+
+/// Every schema after the schemas it references, visiting names alphabetically.
+///
+/// Schemas referencing each other in a cycle are kept together, in alphabetical order, and
+/// marked with `x-normalizer-cycle` listing the whole cycle.
+fn dependency_order(
+    schemas: IndexMap<String, ReferenceOr<Schema>>,
+) -> IndexMap<String, ReferenceOr<Schema>> {
+    let dependencies: IndexMap<String, Vec<String>> = schemas
+        .iter()
+        .map(|(name, schema)| {
+            let mut references = Vec::new();
+            collect_references(
+                &serde_json::to_value(schema).unwrap_or_default(),
+                &mut references,
+            );
+            references.retain(|reference| schemas.contains_key(reference));
+            references.sort();
+            (name.clone(), references)
+        })
+        .collect();
+
+    let mut tarjan = Tarjan {
+        dependencies: &dependencies,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        groups: Vec::new(),
+    };
+    for name in dependencies.keys() {
+        if !tarjan.index.contains_key(name) {
+            tarjan.connect(name);
+        }
+    }
+
+    let mut schemas = schemas;
+    let mut ordered = IndexMap::new();
+
+    for mut group in tarjan.groups {
+        group.sort();
+
+        let is_cycle = group.len() > 1 || dependencies[&group[0]].contains(&group[0]);
+
+        for name in group.iter() {
+            let Some((name, mut schema)) = schemas.shift_remove_entry(name) else {
+                continue;
+            };
+
+            if let (true, ReferenceOr::Item(schema)) = (is_cycle, &mut schema) {
+                schema
+                    .schema_data
+                    .extensions
+                    .insert(CYCLE_EXTENSION.to_string(), serde_json::json!(group));
+            }
+
+            ordered.insert(name, schema);
+        }
+    }
+
+    ordered
+}
+
+/// Tarjan's strongly connected components, which come out dependencies first
+struct Tarjan<'a> {
+    dependencies: &'a IndexMap<String, Vec<String>>,
+    index: HashMap<String, usize>,
+    low: HashMap<String, usize>,
+    stack: Vec<String>,
+    groups: Vec<Vec<String>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, name: &str) {
+        let index = self.index.len();
+        self.index.insert(name.to_string(), index);
+        self.low.insert(name.to_string(), index);
+        self.stack.push(name.to_string());
+
+        for reference in self.dependencies[name].iter() {
+            if !self.index.contains_key(reference) {
+                self.connect(reference);
+                let low = self.low[name].min(self.low[reference]);
+                self.low.insert(name.to_string(), low);
+            } else if self.stack.contains(reference) {
+                let low = self.low[name].min(self.index[reference]);
+                self.low.insert(name.to_string(), low);
+            }
+        }
+
+        if self.low[name] == index {
+            let position = self
+                .stack
+                .iter()
+                .rposition(|member| member == name)
+                .unwrap_or_default();
+            self.groups.push(self.stack.split_off(position));
+        }
+    }
+}
+
+/// Names of the component schemas referenced anywhere in `value`
+fn collect_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter() {
+                match (key.as_str(), child) {
+                    ("$ref", Value::String(reference)) => {
+                        let (name, kind) = parse_reference(reference);
+                        if kind == COMPONENT_SCHEMA && !references.iter().any(|r| r == name) {
+                            references.push(name.to_string());
+                        }
+                    }
+                    (_, child) => collect_references(child, references),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter() {
+                collect_references(item, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schemas(value: Value) -> IndexMap<String, ReferenceOr<Schema>> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/schemas/{}", name)})
+    }
+
+    fn cycle(schemas: &IndexMap<String, ReferenceOr<Schema>>, name: &str) -> Option<Value> {
+        match &schemas[name] {
+            ReferenceOr::Item(schema) => {
+                schema.schema_data.extensions.get(CYCLE_EXTENSION).cloned()
+            }
+            ReferenceOr::Reference { .. } => None,
+        }
+    }
+
+    #[test]
+    fn dependencies_come_first() {
+        let ordered = dependency_order(schemas(json!({
+            "Order": {"type": "object", "properties": {"user": reference("User"), "items": {"type": "array", "items": reference("Item")}}},
+            "User": {"type": "object", "properties": {"address": reference("Address")}},
+            "Item": {"type": "string"},
+            "Address": {"type": "string"},
+        })));
+
+        assert_eq!(
+            ordered.keys().collect::<Vec<_>>(),
+            ["Item", "Address", "User", "Order"]
+        );
+        assert_eq!(cycle(&ordered, "Order"), None);
+    }
+
+    #[test]
+    fn cycles_stay_together_and_are_marked() {
+        let ordered = dependency_order(schemas(json!({
+            "Zoo": {"type": "object", "properties": {"node": reference("Node")}},
+            "Node": {"type": "object", "properties": {"edges": {"type": "array", "items": reference("Edge")}}},
+            "Edge": {"type": "object", "properties": {"to": reference("Node"), "tag": reference("Tag")}},
+            "Tag": {"type": "string"},
+            "Tree": {"type": "object", "properties": {"children": {"type": "array", "items": reference("Tree")}}},
+        })));
+
+        assert_eq!(
+            ordered.keys().collect::<Vec<_>>(),
+            ["Tag", "Edge", "Node", "Zoo", "Tree"]
+        );
+        assert_eq!(cycle(&ordered, "Node"), Some(json!(["Edge", "Node"])));
+        assert_eq!(cycle(&ordered, "Edge"), Some(json!(["Edge", "Node"])));
+        assert_eq!(cycle(&ordered, "Tree"), Some(json!(["Tree"])));
+        assert_eq!(cycle(&ordered, "Zoo"), None);
+        assert_eq!(cycle(&ordered, "Tag"), None);
+    }
+}
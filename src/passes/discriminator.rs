@@ -0,0 +1,81 @@
+use anyhow::Result;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::SchemaKind;
+
+use crate::analysis::discriminator::infer;
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+
+/// Add the discriminator proposed by the `missing-discriminator` rule to every `oneOf` it applies to
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        let ReferenceOr::Item(schema) = slot else {
+            return Ok(());
+        };
+
+        let SchemaKind::OneOf { one_of } = &schema.schema_kind else {
+            return Ok(());
+        };
+
+        if schema.schema_data.discriminator.is_some() {
+            return Ok(());
+        }
+
+        if let Ok(discriminator) = infer(one_of, &components) {
+            println!(
+                "Discriminating {} by {}",
+                origin.pointer, discriminator.property_name
+            );
+
+            schema.schema_data.discriminator = Some(discriminator);
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn adds_inferred_discriminators() {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Cat": {"type": "object", "required": ["kind"], "properties": {"kind": {"type": "string", "enum": ["cat"]}}},
+                "Dog": {"type": "object", "required": ["kind"], "properties": {"kind": {"type": "string", "enum": ["dog"]}}},
+                "Pet": {"oneOf": [{"$ref": "#/components/schemas/Cat"}, {"$ref": "#/components/schemas/Dog"}]},
+                "Loose": {"oneOf": [{"$ref": "#/components/schemas/Cat"}, {"type": "string"}]}
+            }}
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        let schemas = &serde_json::to_value(&openapi).unwrap()["components"]["schemas"];
+        assert_eq!(
+            schemas["Pet"]["discriminator"],
+            json!({
+                "propertyName": "kind",
+                "mapping": {"cat": "#/components/schemas/Cat", "dog": "#/components/schemas/Dog"}
+            })
+        );
+        assert_eq!(schemas["Loose"].get("discriminator"), None);
+    }
+}
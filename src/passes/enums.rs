@@ -0,0 +1,214 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaData;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::passes::union;
+use crate::report::pointer;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::walk::Origin;
+use crate::COMPONENT_SCHEMA;
+
+/// Replace inline enums declared more than once, or matching a component enum, with a `$ref`.
+///
+/// Copies are compared on their type, format and values. A copy with annotations of its own,
+/// such as `default` or `description`, keeps them next to an `allOf` with the reference, since
+/// siblings of a `$ref` are ignored.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    // canonical enum => component name
+    let mut known: HashMap<String, String> = HashMap::new();
+    for (name, schema) in openapi.components.iter().flat_map(|c| c.schemas.iter()) {
+        if let ReferenceOr::Item(schema) = schema {
+            if let Some(bare) = bare_enum(schema) {
+                known
+                    .entry(canonical_json(&bare)?)
+                    .or_insert_with(|| name.clone());
+            }
+        }
+    }
+
+    // canonical enum => first inline copy and number of copies, in document order
+    let mut copies: IndexMap<String, (Origin, Schema, usize)> = IndexMap::new();
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        if let Some(bare) = inline_enum(origin, slot) {
+            copies
+                .entry(canonical_json(&bare)?)
+                .or_insert_with(|| (origin.clone(), bare, 0))
+                .2 += 1;
+        }
+
+        Ok(())
+    })?;
+
+    let components = openapi.components.get_or_insert_with(Default::default);
+
+    for (key, (origin, bare, count)) in copies {
+        if count < 2 || known.contains_key(&key) {
+            continue;
+        }
+
+        let name = naming.enum_name(&origin, &bare, |candidate| {
+            components.schemas.contains_key(candidate)
+        })?;
+
+        components
+            .schemas
+            .insert(name.clone(), ReferenceOr::Item(bare));
+        known.insert(key, name);
+    }
+
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        let Some(bare) = inline_enum(origin, slot) else {
+            return Ok(());
+        };
+
+        if let Some(name) = known.get(&canonical_json(&bare)?) {
+            println!("Extracting enum {} to {}", origin.pointer, name);
+
+            let ReferenceOr::Item(schema) = slot else {
+                return Ok(());
+            };
+
+            // nullable is part of the component
+            let data = SchemaData {
+                nullable: false,
+                ..std::mem::take(&mut schema.schema_data)
+            };
+            *slot = union::collapse(
+                data,
+                ReferenceOr::ref_(&component_ref(COMPONENT_SCHEMA, name)),
+            );
+        }
+
+        Ok(())
+    })
+}
+
+/// The enum in `slot`, unless it is a component schema of its own
+fn inline_enum(origin: &Origin, slot: &ReferenceOr<Schema>) -> Option<Schema> {
+    let ReferenceOr::Item(schema) = slot else {
+        return None;
+    };
+
+    if let Some(name) = origin.name.as_deref() {
+        if origin.pointer == pointer(&["components", COMPONENT_SCHEMA, name]) {
+            return None;
+        }
+    }
+
+    bare_enum(schema)
+}
+
+/// An enum schema stripped down to what makes two copies the same enum
+fn bare_enum(schema: &Schema) -> Option<Schema> {
+    let is_enum = match &schema.schema_kind {
+        SchemaKind::Type(Type::String(string)) => !string.enumeration.is_empty(),
+        SchemaKind::Type(Type::Integer(integer)) => !integer.enumeration.is_empty(),
+        SchemaKind::Type(Type::Number(number)) => !number.enumeration.is_empty(),
+        _ => false,
+    };
+
+    is_enum.then(|| Schema {
+        schema_data: SchemaData {
+            nullable: schema.schema_data.nullable,
+            ..Default::default()
+        },
+        schema_kind: schema.schema_kind.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn extract(document: Value) -> Value {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(document).unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()
+    }
+
+    #[test]
+    fn extracts_copies_anywhere_in_the_document() {
+        let mode = json!({"type": "string", "enum": ["a", "b"]});
+        let document = extract(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/jobs": {"get": {"responses": {"200": {
+                    "description": "ok",
+                    "headers": {"X-Mode": {"schema": mode}}
+                }}}}
+            },
+            "components": {
+                "schemas": {"Job": {"type": "object", "properties": {"mode": mode}}},
+                "responses": {"Accepted": {
+                    "description": "accepted",
+                    "headers": {"X-Mode": {"schema": mode}}
+                }}
+            }
+        }));
+
+        let reference = json!({"$ref": "#/components/schemas/Mode"});
+        assert_eq!(document["components"]["schemas"]["Mode"], mode);
+        assert_eq!(
+            document["components"]["schemas"]["Job"]["properties"]["mode"],
+            reference
+        );
+        assert_eq!(
+            document["paths"]["/jobs"]["get"]["responses"]["200"]["headers"]["X-Mode"]["schema"],
+            reference
+        );
+        assert_eq!(
+            document["components"]["responses"]["Accepted"]["headers"]["X-Mode"]["schema"],
+            reference
+        );
+    }
+
+    #[test]
+    fn matches_component_enums_and_keeps_annotations() {
+        let document = extract(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Color": {"type": "string", "enum": ["red", "green"]},
+                "Car": {"type": "object", "properties": {
+                    "color": {"type": "string", "enum": ["red", "green"], "default": "red"},
+                    "size": {"type": "integer", "enum": [1, 2]}
+                }}
+            }}
+        }));
+
+        let properties = &document["components"]["schemas"]["Car"]["properties"];
+        assert_eq!(
+            properties["color"],
+            json!({"default": "red", "allOf": [{"$ref": "#/components/schemas/Color"}]})
+        );
+        // a single copy stays inline
+        assert_eq!(
+            properties["size"],
+            json!({"type": "integer", "enum": [1, 2]})
+        );
+    }
+}
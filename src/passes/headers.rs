@@ -0,0 +1,182 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::Header;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_HEADER;
+
+/// Move response headers defined more than once, or matching a component header, into
+/// `components.headers` and refer to them from every response.
+///
+/// Only headers of the same name are considered copies, and components are named after the
+/// header, `X-Request-Id` stays `X-Request-Id`.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    // header key => component name
+    let mut known: HashMap<String, String> = HashMap::new();
+    for (name, header) in openapi.components.iter().flat_map(|c| c.headers.iter()) {
+        if let ReferenceOr::Item(header) = header {
+            known
+                .entry(key(name, header)?)
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    // header key => header name and definition of the first copy, and number of copies
+    let mut copies: IndexMap<String, (String, Header, usize)> = IndexMap::new();
+    walk::all_responses_mut(openapi, config, &mut |_origin, response| {
+        let ReferenceOr::Item(response) = response else {
+            return Ok(());
+        };
+
+        for (name, header) in response.headers.iter() {
+            if let ReferenceOr::Item(header) = header {
+                copies
+                    .entry(key(name, header)?)
+                    .or_insert_with(|| (name.clone(), header.clone(), 0))
+                    .2 += 1;
+            }
+        }
+
+        Ok(())
+    })?;
+
+    let components = openapi.components.get_or_insert_with(Default::default);
+
+    for (key, (name, header, count)) in copies {
+        if count < 2 || known.contains_key(&key) {
+            continue;
+        }
+
+        let name = naming.unique(name, &|candidate: &str| {
+            components.headers.contains_key(candidate)
+        });
+
+        components
+            .headers
+            .insert(name.clone(), ReferenceOr::Item(header));
+        known.insert(key, name);
+    }
+
+    walk::all_responses_mut(openapi, config, &mut |origin, response| {
+        let ReferenceOr::Item(response) = response else {
+            return Ok(());
+        };
+
+        for (header_name, header) in response.headers.iter_mut() {
+            let ReferenceOr::Item(definition) = header else {
+                continue;
+            };
+
+            if let Some(name) = known.get(&key(header_name, definition)?) {
+                println!(
+                    "Hoisting header {} to {}",
+                    origin.child(&["headers", header_name]).pointer,
+                    name
+                );
+
+                *header = ReferenceOr::ref_(&component_ref(COMPONENT_HEADER, name));
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Identity of a header definition, the same for copies under any casing of the name
+fn key(name: &str, header: &Header) -> Result<String> {
+    Ok(format!(
+        "{} {}",
+        name.to_lowercase(),
+        canonical_json(header)?
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn hoist(document: Value) -> Value {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(document).unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()
+    }
+
+    #[test]
+    fn hoists_copies_and_matches_component_headers() {
+        let limit = json!({"style": "simple", "schema": {"type": "integer"}});
+        let request_id = json!({"style": "simple", "schema": {"type": "string", "format": "uuid"}});
+        let document = hoist(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {"/users": {
+                "get": {"responses": {"200": {
+                    "description": "ok",
+                    "headers": {"X-Rate-Limit": limit, "X-Request-Id": request_id}
+                }}},
+                "post": {"responses": {"201": {
+                    "description": "created",
+                    "headers": {"x-rate-limit": limit, "Location": {"style": "simple", "schema": {"type": "string"}}}
+                }}}
+            }},
+            "components": {"headers": {"X-Request-Id": request_id}}
+        }));
+
+        let headers = &document["components"]["headers"];
+        assert_eq!(headers["X-Rate-Limit"], limit);
+        assert_eq!(headers.as_object().unwrap().len(), 2);
+
+        let responses = &document["paths"]["/users"];
+        assert_eq!(
+            responses["get"]["responses"]["200"]["headers"],
+            json!({
+                "X-Rate-Limit": {"$ref": "#/components/headers/X-Rate-Limit"},
+                "X-Request-Id": {"$ref": "#/components/headers/X-Request-Id"}
+            })
+        );
+        assert_eq!(
+            responses["post"]["responses"]["201"]["headers"],
+            json!({
+                "x-rate-limit": {"$ref": "#/components/headers/X-Rate-Limit"},
+                "Location": {"style": "simple", "schema": {"type": "string"}}
+            })
+        );
+    }
+
+    #[test]
+    fn headers_with_other_names_are_not_copies() {
+        let header = json!({"style": "simple", "schema": {"type": "string"}});
+        let document = hoist(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {"/users": {"get": {"responses": {
+                "200": {"description": "ok", "headers": {"X-Trace": header}},
+                "404": {"description": "missing", "headers": {"X-Span": header}}
+            }}}}
+        }));
+
+        assert_eq!(document["components"]["headers"], Value::Null);
+        assert_eq!(
+            document["paths"]["/users"]["get"]["responses"]["404"]["headers"]["X-Span"],
+            header
+        );
+    }
+}
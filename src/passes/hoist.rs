@@ -0,0 +1,120 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::is_complex;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::walk::Origin;
+use crate::COMPONENT_SCHEMA;
+
+/// Move complex inline schemas of path and operation parameters, request bodies and responses,
+/// callbacks included, into `components.schemas`.
+///
+/// Identical inline schemas share one component, and reuse an existing component when one matches.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    // taken out while the paths are walked, callbacks in `components` included
+    let mut schemas = std::mem::take(
+        &mut openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .schemas,
+    );
+
+    // canonical schema => component name
+    let mut known: HashMap<String, String> = HashMap::new();
+    for (name, schema) in schemas.iter() {
+        if let ReferenceOr::Item(schema) = schema {
+            known
+                .entry(canonical_json(schema)?)
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    walk::all_path_items_mut(openapi, config, &mut |item_origin, item| {
+        walk::parameter_list_schemas_mut(
+            &mut item.parameters,
+            item_origin,
+            &mut |origin, slot| hoist(slot, origin, naming, &mut schemas, &mut known),
+        )?;
+
+        for (method, operation) in walk::path_item_operations_mut(item) {
+            let origin = item_origin.operation(method, operation);
+
+            walk::operation_schemas_mut(operation, &origin, &mut |origin, slot| {
+                hoist(slot, origin, naming, &mut schemas, &mut known)
+            })?;
+        }
+
+        Ok(())
+    })?;
+
+    openapi
+        .components
+        .get_or_insert_with(Default::default)
+        .schemas = schemas;
+
+    Ok(())
+}
+
+fn hoist(
+    slot: &mut ReferenceOr<Schema>,
+    origin: &Origin,
+    naming: &mut Naming,
+    schemas: &mut IndexMap<String, ReferenceOr<Schema>>,
+    known: &mut HashMap<String, String>,
+) -> Result<()> {
+    let ReferenceOr::Item(schema) = slot else {
+        return Ok(());
+    };
+
+    if !is_complex(schema) {
+        return Ok(());
+    }
+
+    // arrays stay inline, their items become the component
+    if let SchemaKind::Type(Type::Array(array)) = &mut schema.schema_kind {
+        if let Some(items) = array.items.as_mut() {
+            walk::with_unboxed(items, |items| {
+                hoist(items, &origin.child(&["items"]), naming, schemas, known)
+            })?;
+        }
+
+        return Ok(());
+    }
+
+    let key = canonical_json(schema)?;
+
+    let name = match known.get(&key) {
+        Some(name) => name.clone(),
+        None => {
+            let name =
+                naming.hoisted_name(origin, schema, |candidate| schemas.contains_key(candidate))?;
+
+            schemas.insert(name.clone(), ReferenceOr::Item(schema.clone()));
+            known.insert(key, name.clone());
+
+            name
+        }
+    };
+
+    println!("Hoisting {} to {}", origin.pointer, name);
+
+    *slot = ReferenceOr::ref_(&component_ref(COMPONENT_SCHEMA, &name));
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_SCHEMA;
+
+/// Merge structurally identical component schemas into one and point every reference at it.
+///
+/// Repeats until nothing changes, since merging children can make their parents identical.
+pub fn run(
+    openapi: &mut OpenAPI,
+    _config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    loop {
+        let Some(components) = openapi.components.as_mut() else {
+            return Ok(());
+        };
+
+        // canonical schema => names, in document order
+        let mut groups: IndexMap<String, Vec<String>> = IndexMap::new();
+        for (name, schema) in components.schemas.iter() {
+            if let ReferenceOr::Item(schema) = schema {
+                groups
+                    .entry(canonical_json(schema)?)
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+
+        // duplicate name => survivor name
+        let mut renames: HashMap<String, String> = HashMap::new();
+        // survivors picked for earlier groups, which later groups mustn't reuse
+        let mut survivors: HashSet<String> = HashSet::new();
+
+        for names in groups.values().filter(|names| names.len() > 1) {
+            let Some(ReferenceOr::Item(schema)) = components.schemas.get(&names[0]) else {
+                continue;
+            };
+
+            let survivor = naming.merged_name(&names[0], schema, |candidate| {
+                survivors.contains(candidate)
+                    || (components.schemas.contains_key(candidate)
+                        && !names.iter().any(|n| n == candidate))
+            })?;
+            survivors.insert(survivor.clone());
+
+            println!("Merging {} into {}", names.join(", "), survivor);
+
+            for name in names.iter() {
+                if *name != survivor {
+                    renames.insert(name.clone(), survivor.clone());
+                }
+            }
+        }
+
+        if renames.is_empty() {
+            return Ok(());
+        }
+
+        let mut schemas = IndexMap::new();
+        for (name, schema) in std::mem::take(&mut components.schemas) {
+            match renames.get(&name) {
+                // the survivor takes the place of the first duplicate
+                Some(survivor) if !schemas.contains_key(survivor) => {
+                    schemas.insert(survivor.clone(), schema);
+                }
+                Some(_) => {}
+                None => {
+                    schemas.insert(name, schema);
+                }
+            }
+        }
+        components.schemas = schemas;
+
+        let references: HashMap<String, String> = renames
+            .iter()
+            .map(|(name, survivor)| {
+                (
+                    component_ref(COMPONENT_SCHEMA, name),
+                    component_ref(COMPONENT_SCHEMA, survivor),
+                )
+            })
+            .collect();
+
+        walk::rewrite_references(openapi, &|reference| references.get(reference).cloned())?;
+    }
+}
@@ -0,0 +1,170 @@
+pub mod alias;
+pub mod all_of;
+pub mod canonical;
+mod discriminator;
+mod enums;
+mod headers;
+mod hoist;
+mod merge;
+pub mod nullable;
+mod parameters;
+mod path_names;
+mod path_parameters;
+mod responses;
+pub mod union;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use openapiv3::OpenAPI;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::report::Report;
+
+/// A rewrite of the document, run in registration order
+pub struct Pass {
+    pub name: &'static str,
+    /// Whether the pass runs when the configuration doesn't list passes explicitly
+    pub default: bool,
+    pub run: fn(&mut OpenAPI, &Config, &mut Naming, &mut Report) -> Result<()>,
+    /// Rewrite of the serialized document, for what the model can't express such as key order
+    pub render: Option<fn(&mut Value)>,
+}
+
+/// Every known pass, in the order they run
+pub const PASSES: &[Pass] = &[
+    Pass {
+        name: "collapse-aliases",
+        default: true,
+        run: alias::run,
+        render: None,
+    },
+    Pass {
+        name: "rename-path-parameters",
+        default: false,
+        run: path_names::run,
+        render: None,
+    },
+    Pass {
+        name: "lift-path-parameters",
+        default: true,
+        run: path_parameters::lift,
+        render: None,
+    },
+    Pass {
+        name: "simplify-unions",
+        default: true,
+        run: union::run,
+        render: None,
+    },
+    Pass {
+        name: "extract-enums",
+        default: true,
+        run: enums::run,
+        render: None,
+    },
+    Pass {
+        name: "hoist",
+        default: true,
+        run: hoist::run,
+        render: None,
+    },
+    Pass {
+        name: "hoist-headers",
+        default: true,
+        run: headers::run,
+        render: None,
+    },
+    Pass {
+        name: "merge",
+        default: true,
+        run: merge::run,
+        render: None,
+    },
+    Pass {
+        name: "dedupe-responses",
+        default: true,
+        run: responses::run,
+        render: None,
+    },
+    Pass {
+        name: "dedupe-parameters",
+        default: true,
+        run: parameters::run,
+        render: None,
+    },
+    Pass {
+        name: "flatten-all-of",
+        default: false,
+        run: all_of::run,
+        render: None,
+    },
+    Pass {
+        name: "infer-discriminators",
+        default: false,
+        run: discriminator::run,
+        render: None,
+    },
+    Pass {
+        name: "nullable",
+        default: true,
+        run: nullable::run,
+        render: None,
+    },
+    Pass {
+        name: "push-down-path-parameters",
+        default: false,
+        run: path_parameters::push_down,
+        render: None,
+    },
+    Pass {
+        name: "canonicalize",
+        default: true,
+        run: canonical::run,
+        render: Some(canonical::render),
+    },
+];
+
+/// Resolve the configured pass names, or the default set when none are configured
+pub fn selected(config: &Config) -> Result<Vec<&'static Pass>> {
+    match &config.passes {
+        None => Ok(PASSES.iter().filter(|pass| pass.default).collect()),
+        Some(names) => {
+            for name in names.iter() {
+                if !PASSES.iter().any(|pass| pass.name == name) {
+                    return Err(anyhow!(format!("Unknown pass {}", name)));
+                }
+            }
+
+            Ok(PASSES
+                .iter()
+                .filter(|pass| names.iter().any(|name| name == pass.name))
+                .collect())
+        }
+    }
+}
+
+pub fn run(
+    passes: &[&Pass],
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    report: &mut Report,
+) -> Result<()> {
+    for pass in passes.iter() {
+        println!("Running pass {}", pass.name);
+        (pass.run)(openapi, config, naming, report)?;
+    }
+
+    Ok(())
+}
+
+/// Apply the `render` step of every selected pass to the serialized document
+pub fn render(passes: &[&Pass], document: &mut Value) {
+    for pass in passes.iter() {
+        if let Some(render) = pass.render {
+            render(document);
+        }
+    }
+}
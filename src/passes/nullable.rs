@@ -0,0 +1,268 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use openapiv3::AnySchema;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaData;
+use openapiv3::SchemaKind;
+use serde::Deserialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::passes::union;
+use crate::report::Report;
+use crate::walk;
+
+pub const NULLABLE_EXTENSION: &str = "x-nullable";
+
+/// How nullable schemas are written
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Nullable {
+    /// `nullable: true`
+    #[default]
+    Keyword,
+    /// `oneOf` with a `{type: null}` member
+    Union,
+    /// `x-nullable: true`, as written by Swagger 2 converters
+    Extension,
+}
+
+impl FromStr for Nullable {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyword" => Ok(Nullable::Keyword),
+            "union" => Ok(Nullable::Union),
+            "extension" => Ok(Nullable::Extension),
+            _ => Err(anyhow!(format!(
+                "Unknown nullable representation {}, expected keyword, union or extension",
+                s
+            ))),
+        }
+    }
+}
+
+/// Rewrite `nullable: true`, unions with `{type: null}` and `x-nullable` into the configured form
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let representation = config.nullable.unwrap_or_default();
+
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        let before = slot.clone();
+
+        to_keyword(slot);
+
+        match representation {
+            Nullable::Keyword => {}
+            Nullable::Union => to_union(slot),
+            Nullable::Extension => to_extension(slot),
+        }
+
+        if *slot != before {
+            println!("Normalizing nullable at {}", origin.pointer);
+        }
+
+        Ok(())
+    })
+}
+
+/// Whether a schema is nullable in any of the representations
+pub fn is_nullable(schema: &Schema) -> bool {
+    let union_with_null = match &schema.schema_kind {
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => {
+            members.iter().any(union::is_null)
+        }
+        _ => false,
+    };
+
+    schema.schema_data.nullable
+        || schema.schema_data.extensions.get(NULLABLE_EXTENSION) == Some(&Value::Bool(true))
+        || union_with_null
+}
+
+/// Bring any representation to `nullable: true`
+fn to_keyword(slot: &mut ReferenceOr<Schema>) {
+    let ReferenceOr::Item(schema) = slot else {
+        return;
+    };
+
+    if let Some(extension) = schema
+        .schema_data
+        .extensions
+        .shift_remove(NULLABLE_EXTENSION)
+    {
+        schema.schema_data.nullable |= extension == Value::Bool(true);
+    }
+
+    let members = match &mut schema.schema_kind {
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => members,
+        _ => return,
+    };
+
+    // a union of nothing but null has no type to make nullable
+    if !members.iter().any(union::is_null) || members.iter().all(union::is_null) {
+        return;
+    }
+
+    members.retain(|member| !union::is_null(member));
+    schema.schema_data.nullable = true;
+
+    if members.len() == 1 {
+        let member = members.remove(0);
+        let data = std::mem::take(&mut schema.schema_data);
+        *slot = union::collapse(data, member);
+    }
+}
+
+fn to_extension(slot: &mut ReferenceOr<Schema>) {
+    if let ReferenceOr::Item(schema) = slot {
+        if schema.schema_data.nullable {
+            schema.schema_data.nullable = false;
+            schema
+                .schema_data
+                .extensions
+                .insert(NULLABLE_EXTENSION.to_string(), Value::Bool(true));
+        }
+    }
+}
+
+fn to_union(slot: &mut ReferenceOr<Schema>) {
+    let ReferenceOr::Item(schema) = slot else {
+        return;
+    };
+
+    if !schema.schema_data.nullable {
+        return;
+    }
+
+    schema.schema_data.nullable = false;
+
+    let null = ReferenceOr::Item(Schema {
+        schema_data: SchemaData::default(),
+        schema_kind: SchemaKind::Any(AnySchema {
+            typ: Some("null".to_string()),
+            ..Default::default()
+        }),
+    });
+
+    match &mut schema.schema_kind {
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => {
+            members.push(null);
+        }
+        // the wrapper `to_keyword` gives a nullable reference
+        SchemaKind::AllOf { all_of } if all_of.len() == 1 => {
+            let member = all_of.remove(0);
+            schema.schema_kind = SchemaKind::OneOf {
+                one_of: vec![member, null],
+            };
+        }
+        _ => {
+            let inner = std::mem::replace(
+                schema,
+                Schema {
+                    schema_data: SchemaData::default(),
+                    schema_kind: SchemaKind::OneOf { one_of: Vec::new() },
+                },
+            );
+
+            schema.schema_kind = SchemaKind::OneOf {
+                one_of: vec![ReferenceOr::Item(inner), null],
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn normalize(document: Value, representation: Nullable) -> Value {
+        let config = Config {
+            nullable: Some(representation),
+            ..Default::default()
+        };
+        let mut openapi: OpenAPI = serde_json::from_value(document).unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()
+    }
+
+    fn document() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/pets": {"get": {"responses": {"200": {
+                    "description": "ok",
+                    "headers": {"X-Next": {"schema": {"type": "string", "x-nullable": true}}},
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}
+                }}}}
+            },
+            "components": {
+                "schemas": {"Pet": {"type": "object", "properties": {
+                    "name": {"type": "string", "nullable": true},
+                    "tag": {"oneOf": [{"type": "string"}, {"type": "null"}]}
+                }}},
+                "responses": {"Error": {
+                    "description": "error",
+                    "headers": {"X-Trace": {"content": {"text/plain": {"schema": {"type": "string", "x-nullable": true}}}}}
+                }}
+            }
+        })
+    }
+
+    #[test]
+    fn writes_the_keyword_everywhere() {
+        let document = normalize(document(), Nullable::Keyword);
+        let nullable = json!({"type": "string", "nullable": true});
+
+        assert_eq!(
+            document["components"]["schemas"]["Pet"]["properties"]["name"],
+            nullable
+        );
+        assert_eq!(
+            document["components"]["schemas"]["Pet"]["properties"]["tag"],
+            nullable
+        );
+        assert_eq!(
+            document["paths"]["/pets"]["get"]["responses"]["200"]["headers"]["X-Next"]["schema"],
+            nullable
+        );
+        assert_eq!(
+            document["components"]["responses"]["Error"]["headers"]["X-Trace"]["content"]
+                ["text/plain"]["schema"],
+            nullable
+        );
+    }
+
+    #[test]
+    fn writes_unions_and_extensions() {
+        let document = normalize(document(), Nullable::Union);
+        assert_eq!(
+            document["components"]["schemas"]["Pet"]["properties"]["name"],
+            json!({"oneOf": [{"type": "string"}, {"type": "null"}]})
+        );
+
+        let document = normalize(document, Nullable::Extension);
+        assert_eq!(
+            document["paths"]["/pets"]["get"]["responses"]["200"]["headers"]["X-Next"]["schema"],
+            json!({"type": "string", "x-nullable": true})
+        );
+        assert_eq!(
+            document["components"]["schemas"]["Pet"]["properties"]["tag"],
+            json!({"type": "string", "x-nullable": true})
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::Parameter;
+use openapiv3::ReferenceOr;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_PARAM;
+
+/// Move parameters defined identically more than once, or matching a component parameter, into
+/// `components.parameters` and refer to them from every path and operation.
+///
+/// Copies must agree on everything, name, location, schema, style, `required` and the rest.
+/// New components are named after the parameter, `X-Tenant-Id` becomes `XTenantId`.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    // canonical parameter => component name
+    let mut known: HashMap<String, String> = HashMap::new();
+    for (name, parameter) in openapi.components.iter().flat_map(|c| c.parameters.iter()) {
+        if let ReferenceOr::Item(parameter) = parameter {
+            known
+                .entry(canonical_json(parameter)?)
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    // canonical parameter => definition of the first copy, and number of copies
+    let mut copies: IndexMap<String, (Parameter, usize)> = IndexMap::new();
+    walk::all_parameters_mut(openapi, config, &mut |origin, parameter| {
+        let (None, ReferenceOr::Item(parameter)) = (&origin.name, parameter) else {
+            return Ok(());
+        };
+
+        copies
+            .entry(canonical_json(parameter)?)
+            .or_insert_with(|| (parameter.clone(), 0))
+            .1 += 1;
+
+        Ok(())
+    })?;
+
+    let components = openapi.components.get_or_insert_with(Default::default);
+
+    for (key, (parameter, count)) in copies {
+        if count < 2 || known.contains_key(&key) {
+            continue;
+        }
+
+        let name = naming.convert(&[parameter.parameter_data_ref().name.clone()]);
+        let name = naming.unique(name, &|candidate: &str| {
+            components.parameters.contains_key(candidate)
+        });
+
+        components
+            .parameters
+            .insert(name.clone(), ReferenceOr::Item(parameter));
+        known.insert(key, name);
+    }
+
+    walk::all_parameters_mut(openapi, config, &mut |origin, slot| {
+        let (None, ReferenceOr::Item(parameter)) = (&origin.name, &*slot) else {
+            return Ok(());
+        };
+
+        if let Some(name) = known.get(&canonical_json(parameter)?) {
+            println!("Deduplicating parameter {} to {}", origin.pointer, name);
+
+            *slot = ReferenceOr::ref_(&component_ref(COMPONENT_PARAM, name));
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/parameters/{}", name)})
+    }
+
+    #[test]
+    fn shares_identical_copies_only() {
+        let tenant = json!({"name": "X-Tenant-Id", "in": "header", "required": true, "schema": {"type": "string"}, "style": "simple"});
+        let page =
+            json!({"name": "page", "in": "query", "schema": {"type": "integer"}, "style": "form"});
+        let optional_tenant = json!({"name": "X-Tenant-Id", "in": "header", "schema": {"type": "string"}, "style": "simple"});
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/users": {
+                    "parameters": [tenant],
+                    "get": {"parameters": [page], "responses": {}}
+                },
+                "/pets": {"get": {"parameters": [tenant, page], "responses": {}}},
+                "/health": {"get": {"parameters": [optional_tenant], "responses": {}}}
+            },
+            "components": {"parameters": {"Page": page}}
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+        let document = serde_json::to_value(&openapi).unwrap();
+
+        let parameters = &document["components"]["parameters"];
+        assert_eq!(
+            parameters.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["Page", "XTenantId"]
+        );
+        assert_eq!(parameters["XTenantId"], tenant);
+
+        let paths = &document["paths"];
+        assert_eq!(
+            paths["/users"]["parameters"],
+            json!([reference("XTenantId")])
+        );
+        assert_eq!(
+            paths["/users"]["get"]["parameters"],
+            json!([reference("Page")])
+        );
+        assert_eq!(
+            paths["/pets"]["get"]["parameters"],
+            json!([reference("XTenantId"), reference("Page")])
+        );
+        // differs in required, so it is not a copy
+        assert_eq!(
+            paths["/health"]["get"]["parameters"],
+            json!([optional_tenant])
+        );
+    }
+}
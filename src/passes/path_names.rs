@@ -0,0 +1,405 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::Link;
+use openapiv3::LinkOperation;
+use openapiv3::OpenAPI;
+use openapiv3::Operation;
+use openapiv3::Parameter;
+use openapiv3::ReferenceOr;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::analysis::equivalent_paths::template_parts;
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::report::pointer;
+use crate::report::Report;
+use crate::walk;
+
+/// Old and new name of each renamed variable of a path
+type Renamed = Vec<(String, String)>;
+
+/// Give path variables at the same position of sibling paths the same name, the one the first
+/// path uses, so `/users/{id}` and `/users/{userId}/orders` both use `{id}`.
+///
+/// Paths whose renamed template already exists, or whose renamed parameters are references,
+/// are left alone. Links to a renamed operation follow it, their `operationRef` and the keys of
+/// their renamed `parameters` included, and `$request.path` expressions of its links and
+/// callbacks name the new variables.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    // normalized template up to a variable => name of the variable
+    let mut names: HashMap<String, String> = HashMap::new();
+    // path => renamed path, and the old and new name of each variable
+    let mut renames: IndexMap<String, (String, Renamed)> = IndexMap::new();
+
+    for path in openapi.paths.paths.keys() {
+        let (literals, variables) = template_parts(path);
+
+        let renamed: Vec<String> = variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| {
+                let prefix = format!("{}{{}}", literals[..=index].join("{}"));
+                names
+                    .entry(prefix)
+                    .or_insert_with(|| variable.to_string())
+                    .clone()
+            })
+            .collect();
+
+        if config.is_excluded(path) || renamed.iter().zip(variables.iter()).all(|(a, b)| a == b) {
+            continue;
+        }
+
+        let mut new_path = literals[0].to_string();
+        for (name, literal) in renamed.iter().zip(literals[1..].iter()) {
+            new_path.push_str(&format!("{{{}}}{}", name, literal));
+        }
+
+        let pairs = variables
+            .iter()
+            .zip(renamed)
+            .filter(|(old, new)| **old != new.as_str())
+            .map(|(old, new)| (old.to_string(), new))
+            .collect();
+
+        renames.insert(path.clone(), (new_path, pairs));
+    }
+
+    let mut taken: HashSet<String> = openapi.paths.paths.keys().cloned().collect();
+    let mut applied = HashSet::new();
+    // operationId, and pointer prefix of the old path, => new prefix and renamed variables
+    let mut by_id: HashMap<String, Renamed> = HashMap::new();
+    let mut by_ref: Vec<(String, String, Renamed)> = Vec::new();
+
+    for (path, (new_path, pairs)) in renames.iter() {
+        let Some(ReferenceOr::Item(item)) = openapi.paths.paths.get_mut(path) else {
+            continue;
+        };
+
+        let variables = template_parts(new_path).1;
+        let unique: HashSet<_> = variables.iter().collect();
+        if unique.len() != variables.len() || taken.contains(new_path) {
+            println!("Leaving path {}, {} would collide", path, new_path);
+            continue;
+        }
+
+        let by_reference = item
+            .iter()
+            .flat_map(|(_, operation)| operation.parameters.iter())
+            .chain(item.parameters.iter())
+            .filter(|param| matches!(param, ReferenceOr::Reference { .. }))
+            .filter_map(|param| walk::resolve_parameter(&components, param))
+            .any(|param| {
+                matches!(param, Parameter::Path { .. })
+                    && pairs
+                        .iter()
+                        .any(|(old, _)| *old == param.parameter_data_ref().name)
+            });
+        if by_reference {
+            println!(
+                "Leaving path {}, its path parameters are shared references",
+                path
+            );
+            continue;
+        }
+
+        println!("Renaming path {} to {}", path, new_path);
+
+        let rename = |params: &mut Vec<ReferenceOr<Parameter>>| {
+            for param in params.iter_mut() {
+                if let ReferenceOr::Item(Parameter::Path { parameter_data, .. }) = param {
+                    if let Some((_, new)) =
+                        pairs.iter().find(|(old, _)| *old == parameter_data.name)
+                    {
+                        parameter_data.name = new.clone();
+                    }
+                }
+            }
+        };
+
+        rename(&mut item.parameters);
+        for (_, operation) in walk::path_item_operations_mut(item) {
+            rename(&mut operation.parameters);
+            rename_request_expressions(operation, pairs);
+        }
+
+        for (_, operation) in item.iter() {
+            if let Some(id) = operation.operation_id.as_ref() {
+                by_id.insert(id.clone(), pairs.clone());
+            }
+        }
+        by_ref.push((
+            pointer(&["paths", path]),
+            pointer(&["paths", new_path]),
+            pairs.clone(),
+        ));
+
+        taken.remove(path);
+        taken.insert(new_path.clone());
+        applied.insert(path.clone());
+    }
+
+    openapi.paths.paths = std::mem::take(&mut openapi.paths.paths)
+        .into_iter()
+        .map(|(path, item)| match renames.get(&path) {
+            Some((new_path, _)) if applied.contains(&path) => (new_path.clone(), item),
+            _ => (path, item),
+        })
+        .collect();
+
+    if applied.is_empty() {
+        return Ok(());
+    }
+
+    // links anywhere may point at a renamed path, excluded ones too
+    if let Some(components) = openapi.components.as_mut() {
+        for (name, link) in components.links.iter_mut() {
+            if let ReferenceOr::Item(link) = link {
+                let location = pointer(&["components", "links", name]);
+                rewrite_link(link, &location, &by_id, &by_ref);
+            }
+        }
+    }
+
+    walk::all_responses_mut(openapi, &Config::default(), &mut |origin, response| {
+        let ReferenceOr::Item(response) = response else {
+            return Ok(());
+        };
+
+        for (name, link) in response.links.iter_mut() {
+            if let ReferenceOr::Item(link) = link {
+                let location = origin.child(&["links", name]).pointer;
+                rewrite_link(link, &location, &by_id, &by_ref);
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Rename `$request.path.<variable>` in the callback expressions of an operation of a renamed
+/// path and in its responses' links, shared links aside
+fn rename_request_expressions(operation: &mut Operation, pairs: &Renamed) {
+    operation.callbacks = std::mem::take(&mut operation.callbacks)
+        .into_iter()
+        .map(|(name, callback)| {
+            let callback = callback
+                .into_iter()
+                .map(|(expression, item)| (rename_expression(&expression, pairs), item))
+                .collect();
+            (name, callback)
+        })
+        .collect();
+
+    for (_, response) in walk::operation_responses_mut(operation) {
+        let ReferenceOr::Item(response) = response else {
+            continue;
+        };
+
+        for link in response.links.values_mut() {
+            if let ReferenceOr::Item(link) = link {
+                link.parameters
+                    .values_mut()
+                    .chain(link.request_body.iter_mut())
+                    .for_each(|value| rename_in_value(value, pairs));
+            }
+        }
+    }
+}
+
+fn rename_in_value(value: &mut Value, pairs: &Renamed) {
+    match value {
+        Value::String(text) => *text = rename_expression(text, pairs),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| rename_in_value(item, pairs)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|item| rename_in_value(item, pairs)),
+        _ => {}
+    }
+}
+
+/// `text` with every `$request.path.<old>` naming the new variable instead
+fn rename_expression(text: &str, pairs: &Renamed) -> String {
+    const PREFIX: &str = "$request.path.";
+
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(PREFIX) {
+        let (before, expression) = rest.split_at(start + PREFIX.len());
+        result.push_str(before);
+
+        let end = expression
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(expression.len());
+        let (name, after) = expression.split_at(end);
+
+        match pairs.iter().find(|(old, _)| old == name) {
+            Some((_, new)) => result.push_str(new),
+            None => result.push_str(name),
+        }
+        rest = after;
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Point a link at the renamed path of its operation and rename the keys of its `parameters`
+/// after the renamed variables
+fn rewrite_link(
+    link: &mut Link,
+    location: &str,
+    by_id: &HashMap<String, Renamed>,
+    by_ref: &[(String, String, Renamed)],
+) {
+    let pairs = match &mut link.operation {
+        LinkOperation::OperationId(id) => match by_id.get(id) {
+            Some(pairs) => pairs,
+            None => return,
+        },
+        LinkOperation::OperationRef(reference) => {
+            let renamed = by_ref.iter().find_map(|(old, new, pairs)| {
+                let rest = reference.strip_prefix(old.as_str())?;
+                (rest.is_empty() || rest.starts_with('/'))
+                    .then(|| (format!("{}{}", new, rest), pairs))
+            });
+
+            match renamed {
+                Some((new_reference, pairs)) => {
+                    println!("Pointing link {} at {}", location, new_reference);
+                    *reference = new_reference;
+                    pairs
+                }
+                None => return,
+            }
+        }
+    };
+
+    link.parameters = std::mem::take(&mut link.parameters)
+        .into_iter()
+        .map(|(key, value)| {
+            let (prefix, name) = match key.strip_prefix("path.") {
+                Some(name) => ("path.", name),
+                None => ("", key.as_str()),
+            };
+
+            match pairs.iter().find(|(old, _)| old == name) {
+                Some((_, new)) => (format!("{}{}", prefix, new), value),
+                None => (key, value),
+            }
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename(document: Value) -> Value {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(document).unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()
+    }
+
+    fn path_parameter(name: &str) -> Value {
+        json!({"name": name, "in": "path", "required": true, "schema": {"type": "string"}})
+    }
+
+    #[test]
+    fn renames_variables_and_what_refers_to_them() {
+        let document = rename(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/users/{id}": {"get": {
+                    "parameters": [path_parameter("id")],
+                    "responses": {"200": {"description": "ok", "links": {"orders": {
+                        "operationRef": "#/paths/~1users~1{userId}~1orders/get",
+                        "parameters": {"path.userId": "$request.path.id"}
+                    }}}}
+                }},
+                "/users/{userId}/orders": {"post": {
+                    "operationId": "createOrder",
+                    "parameters": [path_parameter("userId")],
+                    "callbacks": {"created": {"{$request.path.userId}/hook": {}}},
+                    "responses": {"201": {"description": "ok", "links": {"again": {
+                        "operationId": "createOrder",
+                        "parameters": {"userId": "$request.path.userId"},
+                        "requestBody": {"owner": "{$request.path.userId}", "userIds": "$request.path.userIds"}
+                    }}}}
+                }}
+            }
+        }));
+
+        let paths = &document["paths"];
+        assert_eq!(
+            paths.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["/users/{id}", "/users/{id}/orders"]
+        );
+        assert_eq!(
+            paths["/users/{id}"]["get"]["responses"]["200"]["links"]["orders"],
+            json!({
+                "operationRef": "#/paths/~1users~1{id}~1orders/get",
+                "parameters": {"path.id": "$request.path.id"}
+            })
+        );
+
+        let operation = &paths["/users/{id}/orders"]["post"];
+        assert_eq!(operation["parameters"][0]["name"], "id");
+        assert_eq!(
+            operation["callbacks"]["created"],
+            json!({"{$request.path.id}/hook": {}})
+        );
+        assert_eq!(
+            operation["responses"]["201"]["links"]["again"],
+            json!({
+                "operationId": "createOrder",
+                "parameters": {"id": "$request.path.id"},
+                "requestBody": {"owner": "{$request.path.id}", "userIds": "$request.path.userIds"}
+            })
+        );
+    }
+
+    #[test]
+    fn leaves_paths_that_would_collide() {
+        let document = rename(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/users/{id}": {"get": {"parameters": [path_parameter("id")], "responses": {}}},
+                "/users/{userId}": {"put": {"parameters": [path_parameter("userId")], "responses": {}}},
+                "/users/{userId}/orders": {"get": {"parameters": [path_parameter("userId")], "responses": {}}}
+            }
+        }));
+
+        assert_eq!(
+            document["paths"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["/users/{id}", "/users/{userId}", "/users/{id}/orders"]
+        );
+        assert_eq!(
+            document["paths"]["/users/{userId}"]["put"]["parameters"][0]["name"],
+            "userId"
+        );
+    }
+}
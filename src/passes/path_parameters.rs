@@ -0,0 +1,175 @@
+use anyhow::Result;
+use openapiv3::OpenAPI;
+
+use crate::analysis::path_parameters;
+use crate::config::Config;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+
+/// Move parameters every operation of a path, or of a callback, defines identically into the
+/// path item's `parameters`
+pub fn lift(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_path_items_mut(openapi, config, &mut |origin, item| {
+        for parameter in path_parameters::shared(item, &components) {
+            if let Some((location, name)) = path_parameters::key(&components, &parameter) {
+                println!(
+                    "Lifting {} parameter {} to path {}",
+                    location,
+                    name,
+                    origin.path.as_deref().unwrap_or_default()
+                );
+            }
+
+            for (_, operation) in walk::path_item_operations_mut(item) {
+                operation
+                    .parameters
+                    .retain(|other| !path_parameters::same(&parameter, other));
+            }
+
+            item.parameters.push(parameter);
+        }
+
+        Ok(())
+    })
+}
+
+/// Copy path-level parameters into every operation of the path, unless the operation overrides
+/// them, and remove them from the path item
+pub fn push_down(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    let components = openapi.components.clone().unwrap_or_default();
+
+    walk::all_path_items_mut(openapi, config, &mut |origin, item| {
+        // without operations there is nowhere to put them
+        if item.parameters.is_empty() || item.iter().next().is_none() {
+            return Ok(());
+        }
+
+        println!(
+            "Pushing {} path-level parameters of {} down to its operations",
+            item.parameters.len(),
+            origin.path.as_deref().unwrap_or_default()
+        );
+
+        let parameters = std::mem::take(&mut item.parameters);
+
+        for (_, operation) in walk::path_item_operations_mut(item) {
+            let inherited: Vec<_> = parameters
+                .iter()
+                .filter(|parameter| {
+                    let Some(parameter_key) = path_parameters::key(&components, parameter) else {
+                        return true;
+                    };
+
+                    !operation.parameters.iter().any(|own| {
+                        path_parameters::key(&components, own).as_ref() == Some(&parameter_key)
+                    })
+                })
+                .cloned()
+                .collect();
+
+            operation.parameters = inherited
+                .into_iter()
+                .chain(std::mem::take(&mut operation.parameters))
+                .collect();
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    type Pass = fn(&mut OpenAPI, &Config, &mut Naming, &mut Report) -> Result<()>;
+
+    fn apply(pass: Pass, item: Value) -> Value {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {"/users/{id}": item}
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        pass(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()["paths"]["/users/{id}"].clone()
+    }
+
+    fn id(description: &str) -> Value {
+        json!({"name": "id", "in": "path", "required": true, "description": description, "schema": {"type": "string"}, "style": "simple"})
+    }
+
+    fn tenant() -> Value {
+        json!({"name": "X-Tenant", "in": "header", "schema": {"type": "string"}, "style": "simple"})
+    }
+
+    fn limit() -> Value {
+        json!({"name": "limit", "in": "query", "schema": {"type": "integer"}, "style": "form"})
+    }
+
+    #[test]
+    fn lifts_parameters_every_operation_shares() {
+        let item = apply(
+            lift,
+            json!({
+                "get": {"parameters": [id("user"), limit()], "responses": {}},
+                "delete": {"parameters": [id("user")], "responses": {}}
+            }),
+        );
+
+        assert_eq!(item["parameters"], json!([id("user")]));
+        assert_eq!(item["get"]["parameters"], json!([limit()]));
+        assert_eq!(item["delete"]["parameters"], Value::Null);
+    }
+
+    #[test]
+    fn leaves_parameters_the_operations_disagree_on() {
+        let item = apply(
+            lift,
+            json!({
+                "get": {"parameters": [id("user")], "responses": {}},
+                "delete": {"parameters": [id("user to delete")], "responses": {}}
+            }),
+        );
+
+        assert_eq!(item["parameters"], Value::Null);
+        assert_eq!(item["get"]["parameters"], json!([id("user")]));
+    }
+
+    #[test]
+    fn pushes_parameters_down_unless_overridden() {
+        let item = apply(
+            push_down,
+            json!({
+                "parameters": [id("user"), tenant()],
+                "get": {"parameters": [id("user to show")], "responses": {}},
+                "post": {"responses": {}}
+            }),
+        );
+
+        assert_eq!(item["parameters"], Value::Null);
+        assert_eq!(
+            item["get"]["parameters"],
+            json!([tenant(), id("user to show")])
+        );
+        assert_eq!(item["post"]["parameters"], json!([id("user"), tenant()]));
+    }
+}
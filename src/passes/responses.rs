@@ -0,0 +1,184 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Response;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+use crate::walk::component_ref;
+use crate::COMPONENT_RESPONSE;
+
+/// Names of the status codes error responses are commonly shared for
+const STATUS_NAMES: &[(&str, &str)] = &[
+    ("400", "Bad Request"),
+    ("401", "Unauthorized"),
+    ("402", "Payment Required"),
+    ("403", "Forbidden"),
+    ("404", "Not Found"),
+    ("405", "Method Not Allowed"),
+    ("406", "Not Acceptable"),
+    ("408", "Request Timeout"),
+    ("409", "Conflict"),
+    ("410", "Gone"),
+    ("412", "Precondition Failed"),
+    ("413", "Payload Too Large"),
+    ("415", "Unsupported Media Type"),
+    ("422", "Unprocessable Entity"),
+    ("429", "Too Many Requests"),
+    ("500", "Internal Server Error"),
+    ("501", "Not Implemented"),
+    ("502", "Bad Gateway"),
+    ("503", "Service Unavailable"),
+    ("504", "Gateway Timeout"),
+];
+
+/// Move responses defined identically by more than one operation, or matching a component
+/// response, into `components.responses` and refer to them from every operation.
+///
+/// New components are named after the status code of the first copy, `401` becomes
+/// `Unauthorized`, or after its description for other codes.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    // canonical response => component name
+    let mut known: HashMap<String, String> = HashMap::new();
+    for (name, response) in openapi.components.iter().flat_map(|c| c.responses.iter()) {
+        if let ReferenceOr::Item(response) = response {
+            known
+                .entry(canonical_json(response)?)
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    // canonical response => status and definition of the first copy, and number of copies
+    let mut copies: IndexMap<String, (String, Response, usize)> = IndexMap::new();
+    walk::all_responses_mut(openapi, config, &mut |origin, response| {
+        let (None, ReferenceOr::Item(response)) = (&origin.name, response) else {
+            return Ok(());
+        };
+
+        copies
+            .entry(canonical_json(response)?)
+            .or_insert_with(|| {
+                (
+                    origin.status.clone().unwrap_or_default(),
+                    response.clone(),
+                    0,
+                )
+            })
+            .2 += 1;
+
+        Ok(())
+    })?;
+
+    let components = openapi.components.get_or_insert_with(Default::default);
+
+    for (key, (status, response, count)) in copies {
+        if count < 2 || known.contains_key(&key) {
+            continue;
+        }
+
+        let name = naming.unique(name(naming, &status, &response), &|candidate: &str| {
+            components.responses.contains_key(candidate)
+        });
+
+        components
+            .responses
+            .insert(name.clone(), ReferenceOr::Item(response));
+        known.insert(key, name);
+    }
+
+    walk::all_responses_mut(openapi, config, &mut |origin, slot| {
+        let (None, ReferenceOr::Item(response)) = (&origin.name, &*slot) else {
+            return Ok(());
+        };
+
+        if let Some(name) = known.get(&canonical_json(response)?) {
+            println!("Deduplicating response {} to {}", origin.pointer, name);
+
+            *slot = ReferenceOr::ref_(&component_ref(COMPONENT_RESPONSE, name));
+        }
+
+        Ok(())
+    })
+}
+
+/// Name of a shared response, from its status code or else its description
+fn name(naming: &Naming, status: &str, response: &Response) -> String {
+    if let Some((_, name)) = STATUS_NAMES.iter().find(|(code, _)| *code == status) {
+        return naming.convert(&[name.to_string()]);
+    }
+
+    let description = response.description.trim_end_matches('.');
+    if !description.is_empty() && description.split_whitespace().count() <= 4 {
+        return naming.convert(&[description.to_string()]);
+    }
+
+    naming.convert(&["Response".to_string(), status.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/responses/{}", name)})
+    }
+
+    #[test]
+    fn shares_copies_named_after_status_or_description() {
+        let unauthorized = json!({"description": "no token"});
+        let users = json!({"description": "Page of users."});
+        let missing = json!({"description": "not found"});
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/users": {"get": {"responses": {"200": users, "401": unauthorized, "404": missing}}},
+                "/admins": {"get": {"responses": {"200": users, "401": unauthorized, "404": missing}}},
+                "/pets": {"get": {"responses": {"200": {"description": "pets"}}}}
+            },
+            "components": {"responses": {"Missing": missing}}
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+        let document = serde_json::to_value(&openapi).unwrap();
+
+        let responses = &document["components"]["responses"];
+        assert_eq!(
+            responses.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["Missing", "PageOfUsers", "Unauthorized"]
+        );
+        assert_eq!(responses["Unauthorized"], unauthorized);
+        assert_eq!(responses["PageOfUsers"], users);
+
+        for path in ["/users", "/admins"] {
+            assert_eq!(
+                document["paths"][path]["get"]["responses"],
+                json!({
+                    "200": reference("PageOfUsers"),
+                    "401": reference("Unauthorized"),
+                    "404": reference("Missing")
+                })
+            );
+        }
+        // a single copy stays inline
+        assert_eq!(
+            document["paths"]["/pets"]["get"]["responses"]["200"],
+            json!({"description": "pets"})
+        );
+    }
+}
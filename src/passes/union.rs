@@ -0,0 +1,219 @@
+use anyhow::Result;
+use openapiv3::AnySchema;
+use openapiv3::OpenAPI;
+use openapiv3::ReferenceOr;
+use openapiv3::Schema;
+use openapiv3::SchemaData;
+use openapiv3::SchemaKind;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::fingerprint::canonical_json;
+use crate::naming::Naming;
+use crate::report::Report;
+use crate::walk;
+
+/// Simplify `oneOf` and `anyOf` unions.
+///
+/// Nested unions of the same kind are flattened, `{type: null}` members become `nullable: true`,
+/// structurally identical members are dropped and unions left with one member collapse into it.
+pub fn run(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    _naming: &mut Naming,
+    _report: &mut Report,
+) -> Result<()> {
+    walk::all_schemas_mut(openapi, config, &mut |origin, slot| {
+        let ReferenceOr::Item(schema) = slot else {
+            return Ok(());
+        };
+
+        let (keyword, members) = match &mut schema.schema_kind {
+            SchemaKind::OneOf { one_of } => ("oneOf", one_of),
+            SchemaKind::AnyOf { any_of } => ("anyOf", any_of),
+            _ => return Ok(()),
+        };
+
+        let before = members.clone();
+
+        flatten(keyword, members);
+
+        // a union of nothing but null is left alone, there'd be no type to make nullable
+        if members.iter().any(|member| !is_null(member)) {
+            let count = members.len();
+            members.retain(|member| !is_null(member));
+            if members.len() < count {
+                schema.schema_data.nullable = true;
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut deduplicated = Vec::new();
+        for member in std::mem::take(members) {
+            if seen.insert(canonical_json(&member)?) {
+                deduplicated.push(member);
+            }
+        }
+        *members = deduplicated;
+
+        let changed = *members != before;
+
+        if members.len() == 1 {
+            let member = members.remove(0);
+            let data = std::mem::take(&mut schema.schema_data);
+            *slot = collapse(data, member);
+        } else if !changed {
+            return Ok(());
+        }
+
+        println!("Simplifying {} at {}", keyword, origin.pointer);
+
+        Ok(())
+    })
+}
+
+/// Splice in the members of inline unions of the same kind that carry nothing but their members
+fn flatten(keyword: &str, members: &mut Vec<ReferenceOr<Schema>>) {
+    let mut flattened = Vec::new();
+
+    for member in std::mem::take(members) {
+        match member {
+            ReferenceOr::Item(Schema {
+                schema_data,
+                schema_kind: SchemaKind::OneOf { one_of: nested },
+            }) if keyword == "oneOf" && schema_data == SchemaData::default() => {
+                flattened.extend(nested)
+            }
+            ReferenceOr::Item(Schema {
+                schema_data,
+                schema_kind: SchemaKind::AnyOf { any_of: nested },
+            }) if keyword == "anyOf" && schema_data == SchemaData::default() => {
+                flattened.extend(nested)
+            }
+            member => flattened.push(member),
+        }
+    }
+
+    *members = flattened;
+}
+
+/// Whether a union member is `{type: null}`
+pub fn is_null(member: &ReferenceOr<Schema>) -> bool {
+    match member {
+        ReferenceOr::Item(Schema {
+            schema_kind: SchemaKind::Any(any),
+            ..
+        }) => {
+            *any == AnySchema {
+                typ: Some("null".to_string()),
+                ..Default::default()
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Replace a single member union with its member, keeping what the union itself said about it
+pub fn collapse(mut data: SchemaData, member: ReferenceOr<Schema>) -> ReferenceOr<Schema> {
+    // a discriminator means nothing without alternatives
+    data.discriminator = None;
+
+    match member {
+        ReferenceOr::Reference { reference } if data == SchemaData::default() => {
+            ReferenceOr::Reference { reference }
+        }
+        // siblings of `$ref` are ignored, so the reference is wrapped to keep them
+        ReferenceOr::Reference { reference } => ReferenceOr::Item(Schema {
+            schema_data: data,
+            schema_kind: SchemaKind::AllOf {
+                all_of: vec![ReferenceOr::Reference { reference }],
+            },
+        }),
+        ReferenceOr::Item(mut schema) => {
+            let member_data = &mut schema.schema_data;
+            member_data.nullable |= data.nullable;
+            member_data.read_only |= data.read_only;
+            member_data.write_only |= data.write_only;
+            member_data.deprecated |= data.deprecated;
+            member_data.external_docs = data.external_docs.or(member_data.external_docs.take());
+            member_data.example = data.example.or(member_data.example.take());
+            member_data.title = data.title.or(member_data.title.take());
+            member_data.description = data.description.or(member_data.description.take());
+            member_data.default = data.default.or(member_data.default.take());
+            member_data.extensions.extend(data.extensions);
+
+            ReferenceOr::Item(schema)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    fn simplify(schemas: Value) -> Value {
+        let config = Config::default();
+        let mut openapi: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {},
+            "components": {"schemas": schemas}
+        }))
+        .unwrap();
+        let mut naming = Naming::from_config(&config).unwrap();
+
+        run(&mut openapi, &config, &mut naming, &mut Report::default()).unwrap();
+
+        serde_json::to_value(&openapi).unwrap()["components"]["schemas"].clone()
+    }
+
+    fn reference(name: &str) -> Value {
+        json!({"$ref": format!("#/components/schemas/{}", name)})
+    }
+
+    #[test]
+    fn flattens_nested_unions_and_drops_null_and_duplicates() {
+        let schemas = simplify(json!({
+            "Pet": {"oneOf": [
+                {"oneOf": [reference("Cat"), reference("Dog")]},
+                reference("Cat"),
+                {"type": "null"}
+            ]},
+            // members of the other kind stay nested
+            "Mixed": {"oneOf": [{"anyOf": [reference("Cat"), reference("Dog")]}, reference("Cat")]}
+        }));
+
+        assert_eq!(
+            schemas["Pet"],
+            json!({"nullable": true, "oneOf": [reference("Cat"), reference("Dog")]})
+        );
+        assert_eq!(
+            schemas["Mixed"],
+            json!({"oneOf": [{"anyOf": [reference("Cat"), reference("Dog")]}, reference("Cat")]})
+        );
+    }
+
+    #[test]
+    fn collapses_single_member_unions() {
+        let schemas = simplify(json!({
+            "Name": {"description": "A name", "anyOf": [{"type": "string"}, {"type": "string"}]},
+            "Owner": {"description": "The owner", "oneOf": [reference("Person"), {"type": "null"}]},
+            "Alias": {"oneOf": [reference("Person")]},
+            "Nothing": {"oneOf": [{"type": "null"}]}
+        }));
+
+        assert_eq!(
+            schemas["Name"],
+            json!({"type": "string", "description": "A name"})
+        );
+        assert_eq!(
+            schemas["Owner"],
+            json!({"description": "The owner", "nullable": true, "allOf": [reference("Person")]})
+        );
+        assert_eq!(schemas["Alias"], reference("Person"));
+        // nothing to make nullable, the member itself is what's left
+        assert_eq!(schemas["Nothing"], json!({"type": "null"}));
+    }
+}
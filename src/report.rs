@@ -0,0 +1,261 @@
+use anyhow::anyhow;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::Config;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Severity::Off),
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(anyhow!(format!(
+                "Unknown severity {}, expected off, info, warning or error",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Off => write!(f, "off"),
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A named check with the severity it reports at unless overridden
+#[derive(Debug)]
+pub struct Rule {
+    pub name: &'static str,
+    pub severity: Severity,
+}
+
+pub const UNUSED_SCHEMA: Rule = Rule {
+    name: "unused-schema",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_PARAM: Rule = Rule {
+    name: "unused-parameter",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_RESPONSE: Rule = Rule {
+    name: "unused-response",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_HEADER: Rule = Rule {
+    name: "unused-header",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_REQUEST_BODY: Rule = Rule {
+    name: "unused-request-body",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_EXAMPLE: Rule = Rule {
+    name: "unused-example",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_LINK: Rule = Rule {
+    name: "unused-link",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_CALLBACK: Rule = Rule {
+    name: "unused-callback",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_SECURITY_SCHEME: Rule = Rule {
+    name: "unused-security-scheme",
+    severity: Severity::Warning,
+};
+
+pub const MISSING_PATH_PARAMETER: Rule = Rule {
+    name: "missing-path-parameter",
+    severity: Severity::Error,
+};
+
+pub const UNKNOWN_PATH_PARAMETER: Rule = Rule {
+    name: "unknown-path-parameter",
+    severity: Severity::Error,
+};
+
+pub const OPTIONAL_PATH_PARAMETER: Rule = Rule {
+    name: "optional-path-parameter",
+    severity: Severity::Error,
+};
+
+/// Every rule that can be referenced from a severity override
+pub const RULES: &[&Rule] = &[
+    &UNUSED_SCHEMA,
+    &UNUSED_PARAM,
+    &UNUSED_RESPONSE,
+    &UNUSED_HEADER,
+    &UNUSED_REQUEST_BODY,
+    &UNUSED_EXAMPLE,
+    &UNUSED_LINK,
+    &UNUSED_CALLBACK,
+    &UNUSED_SECURITY_SCHEME,
+    &MISSING_PATH_PARAMETER,
+    &UNKNOWN_PATH_PARAMETER,
+    &OPTIONAL_PATH_PARAMETER,
+    &crate::analysis::similarity::SIMILAR_SCHEMAS,
+    &crate::passes::all_of::ALL_OF_CONFLICT,
+    &crate::analysis::discriminator::MISSING_DISCRIMINATOR,
+    &crate::analysis::discriminator::UNTAGGED_UNION,
+    &crate::analysis::discriminator::INVALID_DISCRIMINATOR,
+    &crate::analysis::overlap::AMBIGUOUS_UNION,
+    &crate::analysis::nullable::OPTIONAL_NULLABLE,
+    &crate::analysis::default_response::DEFAULT_RESPONSE_MISMATCH,
+    &crate::analysis::path_parameters::REPEATED_PATH_PARAMETER,
+    &crate::analysis::path_parameters::OVERRIDDEN_PATH_PARAMETER,
+    &crate::analysis::equivalent_paths::EQUIVALENT_PATHS,
+    &crate::analysis::links::UNRESOLVED_LINK,
+    &crate::analysis::links::UNKNOWN_LINK_PARAMETER,
+    &crate::analysis::links::INVALID_LINK_EXPRESSION,
+];
+
+#[derive(Debug)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// JSON pointer to the offending node, e.g. `#/components/schemas/Pet`
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    overrides: HashMap<String, Severity>,
+    exclude_paths: Vec<String>,
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        for rule_name in config.severity.keys() {
+            if !RULES.iter().any(|rule| rule.name == rule_name) {
+                return Err(anyhow!(format!(
+                    "Unknown rule {} in severity overrides",
+                    rule_name
+                )));
+            }
+        }
+
+        Ok(Self {
+            overrides: config.severity.clone(),
+            exclude_paths: config.exclude_paths.clone(),
+            findings: Vec::new(),
+        })
+    }
+
+    /// Record a finding, unless its rule is turned off or it lies under an excluded path
+    pub fn add(&mut self, rule: &Rule, location: impl Into<String>, message: impl Into<String>) {
+        let severity = self
+            .overrides
+            .get(rule.name)
+            .copied()
+            .unwrap_or(rule.severity);
+
+        if severity == Severity::Off {
+            return;
+        }
+
+        let location = location.into();
+
+        if let Some(path) = pointer_path(&location) {
+            if self
+                .exclude_paths
+                .iter()
+                .any(|pattern| crate::config::glob_match(pattern, &path))
+            {
+                return;
+            }
+        }
+
+        self.findings.push(Finding {
+            rule: rule.name,
+            severity,
+            location,
+            message: message.into(),
+        });
+    }
+
+    #[cfg(test)]
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Error)
+    }
+
+    pub fn print(&self) {
+        for finding in self.findings.iter() {
+            println!(
+                "{}[{}]: {} at {}",
+                finding.severity, finding.rule, finding.message, finding.location
+            );
+        }
+
+        let count = |severity| {
+            self.findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+
+        println!(
+            "{} errors, {} warnings, {} infos",
+            count(Severity::Error),
+            count(Severity::Warning),
+            count(Severity::Info)
+        );
+    }
+}
+
+/// Build a JSON pointer from unescaped tokens
+pub fn pointer(tokens: &[&str]) -> String {
+    let mut result = String::from("#");
+
+    for token in tokens {
+        result.push('/');
+        result.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    }
+
+    result
+}
+
+/// The path key a `#/paths/...` pointer points into, if any
+pub fn pointer_path(location: &str) -> Option<String> {
+    let token = location.strip_prefix("#/paths/")?.split('/').next()?;
+
+    Some(token.replace("~1", "/").replace("~0", "~"))
+}
@@ -0,0 +1,926 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use openapiv3::AdditionalProperties;
+use openapiv3::Callback;
+use openapiv3::Components;
+use openapiv3::Header;
+use openapiv3::Link;
+use openapiv3::MediaType;
+use openapiv3::OpenAPI;
+use openapiv3::Operation;
+use openapiv3::Parameter;
+use openapiv3::ParameterData;
+use openapiv3::ParameterSchemaOrContent;
+use openapiv3::PathItem;
+use openapiv3::ReferenceOr;
+use openapiv3::Response;
+use openapiv3::Schema;
+use openapiv3::SchemaKind;
+use openapiv3::Type;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::parse_reference;
+use crate::report::pointer;
+use crate::COMPONENT_CALLBACK;
+use crate::COMPONENT_LINK;
+use crate::COMPONENT_PARAM;
+use crate::COMPONENT_RESPONSE;
+use crate::COMPONENT_SCHEMA;
+use crate::COMPONENT_SECURITY_SCHEME;
+
+/// Extension holding the document's OpenAPI 3.1 `webhooks` while passes run, since the parser
+/// only keeps `x-` keys besides the 3.0 fields
+pub const WEBHOOKS_EXTENSION: &str = "x-normalizer-webhooks";
+
+/// Callback for schema slots, with where the slot is
+pub type SchemaVisitor<'a> = dyn FnMut(&Origin, &mut ReferenceOr<Schema>) -> Result<()> + 'a;
+
+/// Where a schema sits in the document, used for naming and reporting
+#[derive(Debug, Clone, Default)]
+pub struct Origin {
+    /// JSON pointer to the schema
+    pub pointer: String,
+    pub path: Option<String>,
+    pub method: Option<String>,
+    pub operation_id: Option<String>,
+    /// Response status code, or `default`
+    pub status: Option<String>,
+    /// `param`, `request` or `response`
+    pub location: Option<String>,
+    pub param_name: Option<String>,
+    /// Name of the innermost object property the schema is, or is nested in
+    pub property: Option<String>,
+    /// Component name, for schemas that already live in `components`
+    pub name: Option<String>,
+}
+
+impl Origin {
+    /// Origin of an operation of the path item at this origin
+    pub fn operation(&self, method: &str, operation: &Operation) -> Self {
+        Self {
+            method: Some(method.to_string()),
+            operation_id: operation.operation_id.clone(),
+            ..self.child(&[method])
+        }
+    }
+
+    /// The same origin, one or more levels deeper
+    pub fn child(&self, tokens: &[&str]) -> Self {
+        Self {
+            pointer: format!("{}{}", self.pointer, &pointer(tokens)[1..]),
+            ..self.clone()
+        }
+    }
+
+    /// Value of a name template variable, `operationId` falls back to method and path
+    pub fn variable(&self, variable: &str) -> Option<String> {
+        match variable {
+            "operationId" => self.operation_id.clone().or_else(|| {
+                Some(format!(
+                    "{} {}",
+                    self.method.as_deref()?,
+                    self.path.as_deref()?
+                ))
+            }),
+            "method" => self.method.clone(),
+            "path" => self.path.clone(),
+            "status" => self.status.clone(),
+            "location" => self.location.clone(),
+            "paramName" => self.param_name.clone(),
+            "property" => self.property.clone(),
+            "name" => self.name.clone(),
+            _ => None,
+        }
+    }
+}
+
+pub fn component_ref(kind: &str, name: &str) -> String {
+    format!("#/components/{}/{}", kind, name)
+}
+
+/// Visit every path item, those of `paths`, of component callbacks and of webhooks, followed by
+/// the path items of their operations' callbacks, skipping excluded paths.
+///
+/// Callback path items sit under their callback expression, which is also their `path`.
+pub fn all_path_items_mut(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    f: &mut dyn FnMut(&Origin, &mut PathItem) -> Result<()>,
+) -> Result<()> {
+    if let Some(components) = openapi.components.as_mut() {
+        for (name, callback) in components.callbacks.iter_mut() {
+            if let ReferenceOr::Item(callback) = callback {
+                let origin = Origin {
+                    pointer: pointer(&["components", COMPONENT_CALLBACK, name]),
+                    ..Default::default()
+                };
+
+                callback_path_items_mut(callback, &origin, f)?;
+            }
+        }
+    }
+
+    for (path, item) in openapi.paths.paths.iter_mut() {
+        if config.is_excluded(path) {
+            continue;
+        }
+
+        let ReferenceOr::Item(item) = item else {
+            continue;
+        };
+
+        let origin = Origin {
+            pointer: pointer(&["paths", path]),
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+
+        path_item_mut(item, &origin, f)?;
+    }
+
+    // named rather than templated, so exclude-paths doesn't apply
+    if let Some(value) = openapi.extensions.get_mut(WEBHOOKS_EXTENSION) {
+        let mut webhooks: IndexMap<String, ReferenceOr<PathItem>> =
+            serde_json::from_value(value.clone())?;
+
+        for (name, item) in webhooks.iter_mut() {
+            let ReferenceOr::Item(item) = item else {
+                continue;
+            };
+
+            let origin = Origin {
+                pointer: pointer(&["webhooks", name]),
+                path: Some(name.clone()),
+                ..Default::default()
+            };
+
+            path_item_mut(item, &origin, f)?;
+        }
+
+        *value = serde_json::to_value(&webhooks)?;
+    }
+
+    Ok(())
+}
+
+fn path_item_mut(
+    item: &mut PathItem,
+    origin: &Origin,
+    f: &mut dyn FnMut(&Origin, &mut PathItem) -> Result<()>,
+) -> Result<()> {
+    f(origin, item)?;
+
+    for (method, operation) in path_item_operations_mut(item) {
+        let operation_origin = origin.operation(method, operation);
+
+        for (name, callback) in operation.callbacks.iter_mut() {
+            callback_path_items_mut(callback, &operation_origin.child(&["callbacks", name]), f)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn callback_path_items_mut(
+    callback: &mut Callback,
+    origin: &Origin,
+    f: &mut dyn FnMut(&Origin, &mut PathItem) -> Result<()>,
+) -> Result<()> {
+    for (expression, item) in callback.iter_mut() {
+        let item_origin = Origin {
+            path: Some(expression.clone()),
+            method: None,
+            operation_id: None,
+            ..origin.child(&[expression])
+        };
+
+        path_item_mut(item, &item_origin, f)?;
+    }
+
+    Ok(())
+}
+
+/// Every operation of a path item, by method
+pub fn path_item_operations_mut(item: &mut PathItem) -> Vec<(&'static str, &mut Operation)> {
+    [
+        ("get", item.get.as_mut()),
+        ("put", item.put.as_mut()),
+        ("post", item.post.as_mut()),
+        ("delete", item.delete.as_mut()),
+        ("options", item.options.as_mut()),
+        ("head", item.head.as_mut()),
+        ("patch", item.patch.as_mut()),
+        ("trace", item.trace.as_mut()),
+    ]
+    .into_iter()
+    .filter_map(|(method, operation)| operation.map(|operation| (method, operation)))
+    .collect()
+}
+
+pub fn parameter_data_mut(parameter: &mut Parameter) -> &mut ParameterData {
+    match parameter {
+        Parameter::Query { parameter_data, .. } => parameter_data,
+        Parameter::Header { parameter_data, .. } => parameter_data,
+        Parameter::Path { parameter_data, .. } => parameter_data,
+        Parameter::Cookie { parameter_data, .. } => parameter_data,
+    }
+}
+
+/// Visit the schema of a parameter, whether given directly or per media type
+pub fn parameter_schemas_mut(
+    data: &mut ParameterData,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    match &mut data.format {
+        ParameterSchemaOrContent::Schema(schema) => f(&origin.child(&["schema"]), schema),
+        ParameterSchemaOrContent::Content(content) => content_schemas_mut(content, origin, f),
+    }
+}
+
+/// Visit the schema of every inline parameter in a list, operation or path level
+pub fn parameter_list_schemas_mut(
+    parameters: &mut [ReferenceOr<Parameter>],
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    for (index, param) in parameters.iter_mut().enumerate() {
+        let ReferenceOr::Item(param) = param else {
+            continue;
+        };
+
+        let data = parameter_data_mut(param);
+        let param_origin = Origin {
+            location: Some("param".to_string()),
+            param_name: Some(data.name.clone()),
+            ..origin.child(&["parameters", &index.to_string()])
+        };
+
+        parameter_schemas_mut(data, &param_origin, f)?;
+    }
+
+    Ok(())
+}
+
+/// Visit the schema of every media type in a `content` map
+pub fn content_schemas_mut(
+    content: &mut IndexMap<String, MediaType>,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    for (content_key, content_media) in content.iter_mut() {
+        if let Some(schema) = content_media.schema.as_mut() {
+            f(&origin.child(&["content", content_key, "schema"]), schema)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Visit the schema of a header, whether given directly or per media type
+pub fn header_schemas_mut(
+    header: &mut Header,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    match &mut header.format {
+        ParameterSchemaOrContent::Schema(schema) => f(&origin.child(&["schema"]), schema),
+        ParameterSchemaOrContent::Content(content) => content_schemas_mut(content, origin, f),
+    }
+}
+
+/// Visit the schema of every media type and every inline header of a response
+pub fn response_schemas_mut(
+    response: &mut Response,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    content_schemas_mut(&mut response.content, origin, f)?;
+
+    for (name, header) in response.headers.iter_mut() {
+        if let ReferenceOr::Item(header) = header {
+            let header_origin = Origin {
+                param_name: Some(name.clone()),
+                ..origin.child(&["headers", name])
+            };
+
+            header_schemas_mut(header, &header_origin, f)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Every response of an operation by status, `default` included under its own key
+pub fn operation_responses_mut(
+    operation: &mut Operation,
+) -> Vec<(String, &mut ReferenceOr<Response>)> {
+    operation
+        .responses
+        .responses
+        .iter_mut()
+        .map(|(status, response)| (status.to_string(), response))
+        .chain(
+            operation
+                .responses
+                .default
+                .as_mut()
+                .map(|response| ("default".to_string(), response)),
+        )
+        .collect()
+}
+
+/// Visit the top level schema of every parameter, request body, response and response header of
+/// an operation
+pub fn operation_schemas_mut(
+    operation: &mut Operation,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    parameter_list_schemas_mut(&mut operation.parameters, origin, f)?;
+
+    if let Some(ReferenceOr::Item(request_body)) = operation.request_body.as_mut() {
+        let request_origin = Origin {
+            location: Some("request".to_string()),
+            ..origin.child(&["requestBody"])
+        };
+
+        content_schemas_mut(&mut request_body.content, &request_origin, f)?;
+    }
+
+    for (status, response) in operation_responses_mut(operation) {
+        let ReferenceOr::Item(response) = response else {
+            continue;
+        };
+
+        let response_origin = Origin {
+            location: Some("response".to_string()),
+            status: Some(status.clone()),
+            ..origin.child(&["responses", &status])
+        };
+
+        response_schemas_mut(response, &response_origin, f)?;
+    }
+
+    Ok(())
+}
+
+/// Visit a schema and every schema nested in it, children before their parents
+pub fn schema_tree_mut(
+    slot: &mut ReferenceOr<Schema>,
+    origin: &Origin,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    if let ReferenceOr::Item(schema) = slot {
+        let keyword = composition_keyword(&schema.schema_kind);
+
+        match &mut schema.schema_kind {
+            SchemaKind::Type(Type::Object(object)) => {
+                for (name, property) in object.properties.iter_mut() {
+                    let property_origin = Origin {
+                        property: Some(name.clone()),
+                        ..origin.child(&["properties", name])
+                    };
+
+                    with_unboxed(property, |property| {
+                        schema_tree_mut(property, &property_origin, f)
+                    })?;
+                }
+
+                if let Some(AdditionalProperties::Schema(schema)) =
+                    object.additional_properties.as_mut()
+                {
+                    schema_tree_mut(schema, &origin.child(&["additionalProperties"]), f)?;
+                }
+            }
+            SchemaKind::Type(Type::Array(array)) => {
+                if let Some(items) = array.items.as_mut() {
+                    with_unboxed(items, |items| {
+                        schema_tree_mut(items, &origin.child(&["items"]), f)
+                    })?;
+                }
+            }
+            SchemaKind::Type(_) => {}
+            SchemaKind::OneOf { one_of: members }
+            | SchemaKind::AllOf { all_of: members }
+            | SchemaKind::AnyOf { any_of: members } => {
+                for (index, member) in members.iter_mut().enumerate() {
+                    schema_tree_mut(member, &origin.child(&[keyword, &index.to_string()]), f)?;
+                }
+            }
+            SchemaKind::Not { not } => schema_tree_mut(not, &origin.child(&["not"]), f)?,
+            SchemaKind::Any(any) => {
+                for (name, property) in any.properties.iter_mut() {
+                    let property_origin = Origin {
+                        property: Some(name.clone()),
+                        ..origin.child(&["properties", name])
+                    };
+
+                    with_unboxed(property, |property| {
+                        schema_tree_mut(property, &property_origin, f)
+                    })?;
+                }
+
+                if let Some(AdditionalProperties::Schema(schema)) =
+                    any.additional_properties.as_mut()
+                {
+                    schema_tree_mut(schema, &origin.child(&["additionalProperties"]), f)?;
+                }
+
+                if let Some(items) = any.items.as_mut() {
+                    with_unboxed(items, |items| {
+                        schema_tree_mut(items, &origin.child(&["items"]), f)
+                    })?;
+                }
+
+                for (keyword, members) in [
+                    ("oneOf", &mut any.one_of),
+                    ("allOf", &mut any.all_of),
+                    ("anyOf", &mut any.any_of),
+                ] {
+                    for (index, member) in members.iter_mut().enumerate() {
+                        schema_tree_mut(member, &origin.child(&[keyword, &index.to_string()]), f)?;
+                    }
+                }
+
+                if let Some(not) = any.not.as_mut() {
+                    schema_tree_mut(not, &origin.child(&["not"]), f)?;
+                }
+            }
+        }
+    }
+
+    f(origin, slot)
+}
+
+fn composition_keyword(kind: &SchemaKind) -> &'static str {
+    match kind {
+        SchemaKind::OneOf { .. } => "oneOf",
+        SchemaKind::AllOf { .. } => "allOf",
+        SchemaKind::AnyOf { .. } => "anyOf",
+        _ => "",
+    }
+}
+
+/// Run `f` on a boxed schema slot as if it wasn't boxed
+pub fn with_unboxed(
+    slot: &mut ReferenceOr<Box<Schema>>,
+    f: impl FnOnce(&mut ReferenceOr<Schema>) -> Result<()>,
+) -> Result<()> {
+    let mut unboxed = std::mem::replace(slot, ReferenceOr::ref_("")).unbox();
+    let result = f(&mut unboxed);
+
+    *slot = match unboxed {
+        ReferenceOr::Reference { reference } => ReferenceOr::Reference { reference },
+        ReferenceOr::Item(schema) => ReferenceOr::boxed_item(schema),
+    };
+
+    result
+}
+
+/// Visit every schema in the document, nested ones included, skipping excluded paths
+pub fn all_schemas_mut(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    f: &mut SchemaVisitor,
+) -> Result<()> {
+    let mut tree =
+        |origin: &Origin, slot: &mut ReferenceOr<Schema>| schema_tree_mut(slot, origin, f);
+
+    if let Some(components) = openapi.components.as_mut() {
+        for (name, schema) in components.schemas.iter_mut() {
+            let origin = Origin {
+                pointer: pointer(&["components", COMPONENT_SCHEMA, name]),
+                name: Some(name.clone()),
+                ..Default::default()
+            };
+
+            tree(&origin, schema)?;
+        }
+
+        for (name, param) in components.parameters.iter_mut() {
+            if let ReferenceOr::Item(param) = param {
+                let data = parameter_data_mut(param);
+                let origin = Origin {
+                    pointer: pointer(&["components", "parameters", name]),
+                    location: Some("param".to_string()),
+                    param_name: Some(data.name.clone()),
+                    ..Default::default()
+                };
+
+                parameter_schemas_mut(data, &origin, &mut tree)?;
+            }
+        }
+
+        for (name, header) in components.headers.iter_mut() {
+            if let ReferenceOr::Item(header) = header {
+                let origin = Origin {
+                    pointer: pointer(&["components", "headers", name]),
+                    ..Default::default()
+                };
+
+                header_schemas_mut(header, &origin, &mut tree)?;
+            }
+        }
+
+        for (name, request_body) in components.request_bodies.iter_mut() {
+            if let ReferenceOr::Item(request_body) = request_body {
+                let origin = Origin {
+                    pointer: pointer(&["components", "requestBodies", name]),
+                    location: Some("request".to_string()),
+                    ..Default::default()
+                };
+
+                content_schemas_mut(&mut request_body.content, &origin, &mut tree)?;
+            }
+        }
+
+        for (name, response) in components.responses.iter_mut() {
+            if let ReferenceOr::Item(response) = response {
+                let origin = Origin {
+                    pointer: pointer(&["components", "responses", name]),
+                    location: Some("response".to_string()),
+                    ..Default::default()
+                };
+
+                response_schemas_mut(response, &origin, &mut tree)?;
+            }
+        }
+    }
+
+    all_path_items_mut(openapi, config, &mut |item_origin, item| {
+        parameter_list_schemas_mut(&mut item.parameters, item_origin, &mut tree)?;
+
+        for (method, operation) in path_item_operations_mut(item) {
+            let origin = item_origin.operation(method, operation);
+
+            operation_schemas_mut(operation, &origin, &mut tree)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Visit every response slot of `components.responses` and of operations, skipping excluded paths
+pub fn all_responses_mut(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    f: &mut dyn FnMut(&Origin, &mut ReferenceOr<Response>) -> Result<()>,
+) -> Result<()> {
+    if let Some(components) = openapi.components.as_mut() {
+        for (name, response) in components.responses.iter_mut() {
+            let origin = Origin {
+                pointer: pointer(&["components", COMPONENT_RESPONSE, name]),
+                location: Some("response".to_string()),
+                name: Some(name.clone()),
+                ..Default::default()
+            };
+
+            f(&origin, response)?;
+        }
+    }
+
+    all_path_items_mut(openapi, config, &mut |item_origin, item| {
+        for (method, operation) in path_item_operations_mut(item) {
+            let origin = item_origin.operation(method, operation);
+
+            for (status, response) in operation_responses_mut(operation) {
+                let response_origin = Origin {
+                    location: Some("response".to_string()),
+                    status: Some(status.clone()),
+                    ..origin.child(&["responses", &status])
+                };
+
+                f(&response_origin, response)?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Visit every parameter slot of `components.parameters`, of path items and of operations,
+/// skipping excluded paths
+pub fn all_parameters_mut(
+    openapi: &mut OpenAPI,
+    config: &Config,
+    f: &mut dyn FnMut(&Origin, &mut ReferenceOr<Parameter>) -> Result<()>,
+) -> Result<()> {
+    if let Some(components) = openapi.components.as_mut() {
+        for (name, parameter) in components.parameters.iter_mut() {
+            let origin = Origin {
+                pointer: pointer(&["components", COMPONENT_PARAM, name]),
+                location: Some("param".to_string()),
+                name: Some(name.clone()),
+                ..Default::default()
+            };
+
+            f(&origin, parameter)?;
+        }
+    }
+
+    all_path_items_mut(openapi, config, &mut |item_origin, item| {
+        for (index, parameter) in item.parameters.iter_mut().enumerate() {
+            let origin = Origin {
+                location: Some("param".to_string()),
+                ..item_origin.child(&["parameters", &index.to_string()])
+            };
+
+            f(&origin, parameter)?;
+        }
+
+        for (method, operation) in path_item_operations_mut(item) {
+            let operation_origin = item_origin.operation(method, operation);
+
+            for (index, parameter) in operation.parameters.iter_mut().enumerate() {
+                let origin = Origin {
+                    location: Some("param".to_string()),
+                    ..operation_origin.child(&["parameters", &index.to_string()])
+                };
+
+                f(&origin, parameter)?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Visit every schema in the document like `all_schemas_mut`, without changing it
+pub fn all_schemas(openapi: &OpenAPI, config: &Config, f: &mut dyn FnMut(&Origin, &Schema)) {
+    // the visitors are written once, for mutable slots, so walk a copy
+    let mut document = openapi.clone();
+
+    // can't fail, the visitor never does
+    let _ = all_schemas_mut(&mut document, config, &mut |origin, slot| {
+        if let ReferenceOr::Item(schema) = slot {
+            f(origin, schema);
+        }
+
+        Ok(())
+    });
+}
+
+/// Follow a local schema reference, through aliases, to the schema it names
+pub fn resolve_schema<'a>(components: &'a Components, reference: &str) -> Option<&'a Schema> {
+    let mut reference = reference;
+
+    // an alias chain can't be longer than the number of components, unless it loops
+    for _ in 0..=components.schemas.len() {
+        let (name, kind) = parse_reference(reference);
+
+        if kind != COMPONENT_SCHEMA || !reference.starts_with("#/components/") {
+            return None;
+        }
+
+        match components.schemas.get(name)? {
+            ReferenceOr::Item(schema) => return Some(schema),
+            ReferenceOr::Reference { reference: next } => reference = next,
+        }
+    }
+
+    None
+}
+
+/// Follow a response reference, through aliases, to the response it names
+pub fn resolve_response<'a>(
+    components: &'a Components,
+    response: &'a ReferenceOr<Response>,
+) -> Option<&'a Response> {
+    let mut response = response;
+
+    for _ in 0..=components.responses.len() {
+        match response {
+            ReferenceOr::Item(response) => return Some(response),
+            ReferenceOr::Reference { reference } => {
+                let (name, kind) = parse_reference(reference);
+                if kind != COMPONENT_RESPONSE {
+                    return None;
+                }
+
+                response = components.responses.get(name)?;
+            }
+        }
+    }
+
+    None
+}
+
+/// Follow a link reference, through aliases, to the link it names
+pub fn resolve_link<'a>(
+    components: &'a Components,
+    link: &'a ReferenceOr<Link>,
+) -> Option<&'a Link> {
+    let mut link = link;
+
+    for _ in 0..=components.links.len() {
+        match link {
+            ReferenceOr::Item(link) => return Some(link),
+            ReferenceOr::Reference { reference } => {
+                let (name, kind) = parse_reference(reference);
+                if kind != COMPONENT_LINK {
+                    return None;
+                }
+
+                link = components.links.get(name)?;
+            }
+        }
+    }
+
+    None
+}
+
+/// Follow a parameter reference, through aliases, to the parameter it names
+pub fn resolve_parameter<'a>(
+    components: &'a Components,
+    parameter: &'a ReferenceOr<Parameter>,
+) -> Option<&'a Parameter> {
+    let mut parameter = parameter;
+
+    for _ in 0..=components.parameters.len() {
+        match parameter {
+            ReferenceOr::Item(parameter) => return Some(parameter),
+            ReferenceOr::Reference { reference } => {
+                let (name, kind) = parse_reference(reference);
+                if kind != COMPONENT_PARAM {
+                    return None;
+                }
+
+                parameter = components.parameters.get(name)?;
+            }
+        }
+    }
+
+    None
+}
+
+/// What identifies a parameter within an operation, its location and name
+pub fn parameter_key(parameter: &Parameter) -> (&'static str, String) {
+    let location = match parameter {
+        Parameter::Query { .. } => "query",
+        Parameter::Header { .. } => "header",
+        Parameter::Path { .. } => "path",
+        Parameter::Cookie { .. } => "cookie",
+    };
+
+    (location, parameter.parameter_data_ref().name.clone())
+}
+
+/// Every component reference in the document, as `#/components/{kind}/{name}`.
+///
+/// Discriminator mappings and security requirements count as references. A component's
+/// references to itself don't.
+pub fn references(openapi: &OpenAPI) -> HashSet<String> {
+    let mut references = HashSet::new();
+
+    let Ok(Value::Object(mut document)) = serde_json::to_value(openapi) else {
+        return references;
+    };
+
+    let components = document.shift_remove("components");
+    collect_references(&Value::Object(document), None, false, &mut references);
+
+    if let Some(Value::Object(kinds)) = components {
+        for (kind, components) in kinds.iter() {
+            let Value::Object(components) = components else {
+                continue;
+            };
+
+            for (name, component) in components.iter() {
+                let own = component_ref(kind, name);
+                collect_references(component, Some(&own), false, &mut references);
+            }
+        }
+    }
+
+    references
+}
+
+fn collect_references(
+    value: &Value,
+    own: Option<&str>,
+    names: bool,
+    references: &mut HashSet<String>,
+) {
+    let add = |references: &mut HashSet<String>, reference: &str| {
+        if Some(reference) != own {
+            references.insert(reference.to_string());
+        }
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter() {
+                match (key.as_str(), child) {
+                    ("$ref", Value::String(reference)) if !names => add(references, reference),
+                    ("discriminator", Value::Object(discriminator)) if !names => {
+                        if let Some(Value::Object(mapping)) = discriminator.get("mapping") {
+                            for target in mapping.values().filter_map(Value::as_str) {
+                                if target.starts_with('#') {
+                                    add(references, target);
+                                } else {
+                                    add(references, &component_ref(COMPONENT_SCHEMA, target));
+                                }
+                            }
+                        }
+
+                        collect_references(child, own, false, references);
+                    }
+                    ("security", Value::Array(requirements)) if !names => {
+                        for requirement in requirements.iter().filter_map(Value::as_object) {
+                            for scheme in requirement.keys() {
+                                add(
+                                    references,
+                                    &component_ref(COMPONENT_SECURITY_SCHEME, scheme),
+                                );
+                            }
+                        }
+
+                        collect_references(child, own, false, references);
+                    }
+                    (key, child) => {
+                        collect_references(child, own, is_name_map(key, names), references)
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter() {
+                collect_references(item, own, false, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace every `$ref`, including discriminator mappings, for which `rewrite` returns a new target
+pub fn rewrite_references(
+    openapi: &mut OpenAPI,
+    rewrite: &dyn Fn(&str) -> Option<String>,
+) -> Result<()> {
+    let mut value = serde_json::to_value(&*openapi)?;
+
+    rewrite_value(&mut value, false, rewrite);
+
+    *openapi = serde_json::from_value(value)?;
+
+    Ok(())
+}
+
+/// Keys whose object value is keyed by names, where a `discriminator` or `security` key is
+/// a property or component rather than a keyword
+const NAME_MAPS: &[&str] = &[
+    "properties",
+    "schemas",
+    "parameters",
+    "responses",
+    "headers",
+    "requestBodies",
+    "examples",
+    "links",
+    "callbacks",
+    "securitySchemes",
+];
+
+/// Whether the keys of the value under `key` are names, given whether the keys of its parent are
+fn is_name_map(key: &str, names: bool) -> bool {
+    !names && NAME_MAPS.contains(&key)
+}
+
+fn rewrite_value(value: &mut Value, names: bool, rewrite: &dyn Fn(&str) -> Option<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match (key.as_str(), child) {
+                    ("$ref", Value::String(reference)) if !names => {
+                        if let Some(target) = rewrite(reference) {
+                            *reference = target;
+                        }
+                    }
+                    ("discriminator", child) if !names && child.is_object() => {
+                        if let Some(Value::Object(mapping)) = child.get_mut("mapping") {
+                            for reference in mapping.values_mut() {
+                                if let Value::String(reference) = reference {
+                                    if let Some(target) = rewrite(reference) {
+                                        *reference = target;
+                                    }
+                                }
+                            }
+                        }
+
+                        rewrite_value(child, false, rewrite);
+                    }
+                    (key, child) => rewrite_value(child, is_name_map(key, names), rewrite),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                rewrite_value(item, false, rewrite);
+            }
+        }
+        _ => {}
+    }
+}